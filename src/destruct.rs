@@ -1,97 +1,2183 @@
 use super::*;
+use std::borrow::Cow;
 
 pub fn impl_destruct_macro(input: &DeriveInput) -> TokenStream {
     let name = &input.ident;
+    if matches!(input.data, Data::Enum(_)) {
+        return syn::Error::new(
+            name.span(),
+            "Destruct can only be derived for structs and unions",
+        )
+        .to_compile_error();
+    }
+    let options = match utils::get_container_attributes(&input.attrs) {
+        Ok(options) => options,
+        Err(err) => return err.to_compile_error(),
+    };
+    // Parsed once for every pass over the fields
+    let attrs = match field_attributes(&input.data) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error(),
+    };
+    if matches!(input.data, Data::Union(_)) && (options.builder || options.defer) {
+        let message = "Union destruct cannot be combined with `builder` or `defer`";
+        return syn::Error::new(name.span(), message).to_compile_error();
+    }
+    if options.require_repr_c && !has_repr_c(&input.attrs) {
+        let message =
+            "Structure requires `#[repr(C)]` or `#[repr(transparent)]` for a stable layout";
+        return syn::Error::new(name.span(), message).to_compile_error();
+    }
+    // The worker thread and its queue are shared by every instantiation
+    if options.defer && !input.generics.params.is_empty() {
+        let message = "Deferred destruct does not support generic structures";
+        return syn::Error::new_spanned(&input.generics, message).to_compile_error();
+    }
+    // The worker thread frees the pointers after the structure is gone
+    if options.defer && options.zero_on_drop {
+        let message = "Deferred destruct cannot be combined with `zero_on_drop`";
+        return syn::Error::new(name.span(), message).to_compile_error();
+    }
+    if options.defer && options.with.is_some() {
+        let message = "Deferred destruct cannot be combined with `with`";
+        return syn::Error::new(name.span(), message).to_compile_error();
+    }
+    if options.defer && options.iterative {
+        let message = "Deferred destruct cannot be combined with `iterative`";
+        return syn::Error::new(name.span(), message).to_compile_error();
+    }
 
-    let destructors = field_destructors(&input.data);
+    // A single function frees the whole structure, ignoring the fields
+    let duplicates = duplicate_warnings(&attrs);
+    let destructors = match options.with {
+        Some(ref function) => quote! {
+            #function(self as *mut Self);
+        },
+        None if options.defer => {
+            field_destructors(name, &input.data, &attrs, &options, Mode::Defer)
+        }
+        None => field_destructors(name, &input.data, &attrs, &options, Mode::Free),
+    };
+    let generics = &input.generics;
+    let partial = partial_destructor(name, generics, &input.data, &attrs, &options);
+    let builder = builder(name, generics, &input.vis, &input.data, &attrs, &options);
+    let into_ffi = into_ffi(name, generics, &input.vis, &options);
+    let destructible = destructible(name, generics, &options);
+    let report = report_count(name, generics, &input.data, &attrs, &options);
+    let gauge = live_gauge(name, generics, &options);
+    let schema = ownership_schema(name, generics, &input.data, &attrs, &options);
+    let descendants = iterative_destructor(&options);
+    let release = release_destructor(&options);
+
+    // Statements after everything is freed
+    let mut finally = Vec::new();
+    if let Some(ref sender) = options.notify_sender {
+        finally.push(quote! {
+            let _ = self.#sender.send(());
+        });
+    }
+
+    // Nothing to do for the structure, unit structures are empty on purpose
+    let unit = matches!(
+        input.data,
+        Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Unit,
+            ..
+        })
+    );
+    let warning = if unit {
+        TokenStream::new()
+    } else if destructors.is_empty()
+        && descendants.is_empty()
+        && release.is_empty()
+        && finally.is_empty()
+    {
+        if options.pod_ok {
+            // No `Drop`, but the constructors of `extern_c_constructor!` still use the gauge
+            let cfg = options.cfg.as_ref().map(|cfg| quote! { #[cfg(#cfg)] });
+            return if gauge.is_empty() {
+                gauge
+            } else {
+                quote! {
+                    #cfg
+                    #gauge
+                }
+            };
+        }
+        pointerless_warning(name)
+    } else {
+        TokenStream::new()
+    };
+
+    // Log the bytes freed for the fields
+    let destructors =
+        if cfg!(feature = "size-log") && options.with.is_none() && !destructors.is_empty() {
+            let message = format!("destruct: {} freed {{}} bytes", name);
+            quote! {
+                #[allow(unused_mut)]
+                let mut bytes: usize = 0;
+                #destructors
+                ::std::eprintln!(#message, bytes);
+            }
+        } else {
+            destructors
+        };
+
+    // Track the live objects, also on the early returns dropping the object without freeing
+    let release_gauge = if cfg!(feature = "gauge") {
+        quote! {
+            let _ = Self::live_gauge().fetch_update(
+                ::std::sync::atomic::Ordering::Relaxed,
+                ::std::sync::atomic::Ordering::Relaxed,
+                |live| live.checked_sub(1),
+            );
+        }
+    } else {
+        TokenStream::new()
+    };
+    finally.push(release_gauge.clone());
+
+    // Checks returning early before anything is freed
+    let mut guards = Vec::new();
+    if let (true, Some(level)) = (cfg!(feature = "log"), &options.log) {
+        guards.push(quote! {
+            ::log::#level!("dropping {}", ::std::stringify!(#name));
+        });
+    }
+    if let Some(ref consumed) = options.consumed_flag {
+        guards.push(quote! {
+            if self.#consumed {
+                #release_gauge
+                return;
+            }
+        });
+    }
+    if let (Some(canary), Some(value)) = (&options.canary, &options.value) {
+        let message = format!(
+            "Canary `{}` of `{}` does not match, skip destructing",
+            canary, name
+        );
+        guards.push(quote! {
+            if self.#canary != (#value) {
+                if cfg!(debug_assertions) {
+                    panic!(#message);
+                }
+                return;
+            }
+        });
+    }
+    if let Some(ref poisoned) = options.skip_if_poisoned {
+        let message = format!("`{}` is poisoned, leaking its resources", name);
+        guards.push(quote! {
+            if self.#poisoned {
+                ::std::eprintln!(#message);
+                #release_gauge
+                return;
+            }
+        });
+    }
+    if let Some(ref refcount) = options.refcount {
+        // Every reference runs `drop` on the same structure, which must not drop other fields
+        let fields = match input.data {
+            Data::Struct(ref data) => data.fields.iter().collect(),
+            _ => Vec::new(),
+        };
+        for f in fields.into_iter().filter(|f| pointer_type(&f.ty).is_none()) {
+            let ty = &f.ty;
+            let message = format!(
+                "The fields of `{}` other than pointers must not need dropping with `refcount`",
+                name
+            );
+            guards.push(quote_spanned! { ty.span() =>
+                const { assert!(!::std::mem::needs_drop::<#ty>(), #message) };
+            });
+        }
+        guards.push(quote! {
+            if self.#refcount.fetch_sub(1, ::std::sync::atomic::Ordering::AcqRel) != 1 {
+                #release_gauge
+                return;
+            }
+        });
+    }
+
+    // Only generate the destructor under the given configuration
+    let cfg = options.cfg.as_ref().map(|cfg| quote! { #[cfg(#cfg)] });
+    let with_cfg = |item: TokenStream| {
+        if item.is_empty() {
+            item
+        } else {
+            quote! {
+                #cfg
+                #item
+            }
+        }
+    };
+    let partial = with_cfg(partial);
+    let builder = with_cfg(builder);
+    let into_ffi = with_cfg(into_ffi);
+    let destructible = with_cfg(destructible);
+    let report = with_cfg(report);
+    let gauge = with_cfg(gauge);
+    let schema = with_cfg(schema);
+    let body = if release.is_empty() && descendants.is_empty() && destructors.is_empty() {
+        // Nothing unsafe to do, e.g. only `#[no_drop]` fields
+        TokenStream::new()
+    } else {
+        quote! {
+            unsafe {
+                #release
+                #descendants
+                #destructors
+            }
+        }
+    };
+    let defer = with_cfg(deferred_destructor(name, &input.data, &attrs, &options));
+    // The raw pointers moved to the worker thread
+    let body = if options.defer && !body.is_empty() {
+        quote! {
+            struct Pointer<P>(P);
+            impl<P> Pointer<P> {
+                // Called in the job, so it captures the whole `Pointer` and not only the field
+                fn get(self) -> P {
+                    self.0
+                }
+            }
+            // Sending the pointer to free the pointee elsewhere is sound if the pointee is `Send`
+            unsafe impl<T: ?::std::marker::Sized + ::std::marker::Send> ::std::marker::Send
+                for Pointer<*mut T>
+            {
+            }
+            unsafe impl<T: ?::std::marker::Sized + ::std::marker::Send> ::std::marker::Send
+                for Pointer<*const T>
+            {
+            }
+
+            #body
+        }
+    } else {
+        body
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut drop_body = quote! {
+        #(#guards)*
+        #body
+        #(#finally)*
+    };
+    let manual = if options.manual {
+        let manual = manual_destructor(name, generics, &input.data, drop_body);
+        drop_body = quote! {
+            self.destruct_manually();
+        };
+        with_cfg(manual)
+    } else {
+        TokenStream::new()
+    };
 
     quote! {
-        impl ::std::ops::Drop for #name {
+        #warning
+        #duplicates
+        #partial
+        #manual
+        #builder
+        #into_ffi
+        #destructible
+        #report
+        #gauge
+        #schema
+        #defer
+        #cfg
+        // Generated code must not break the lint settings of the crate using the derive
+        #[allow(unused_unsafe)]
+        impl #impl_generics ::std::ops::Drop for #name #ty_generics #where_clause {
             fn drop(&mut self) {
-                unsafe {
-                    #destructors
-                }
+                #drop_body
             }
         }
     }
 }
 
+/// Parse the helper attributes of every field in declaration order, combining their errors.
+fn field_attributes(data: &Data) -> syn::Result<Vec<utils::FieldAttributes>> {
+    let fields = match *data {
+        Data::Struct(ref data) => data.fields.iter().collect(),
+        Data::Union(ref data) => data.fields.named.iter().collect(),
+        Data::Enum(_) => Vec::new(),
+    };
+    let mut attrs = Vec::with_capacity(fields.len());
+    let mut errors: Option<syn::Error> = None;
+    for f in fields {
+        match utils::get_field_attributes(&f.attrs) {
+            Ok(field) => attrs.push(field),
+            Err(err) => match errors {
+                Some(ref mut errors) => errors.combine(err),
+                None => errors = Some(err),
+            },
+        }
+    }
+    errors.map_or(Ok(attrs), Err)
+}
+
+/// Options of the `#[ffi_destruct(...)]` attribute macro.
+#[derive(Default)]
+pub struct AttributeOptions {
+    /// `extern_c`: Also generate the extern "C" destructor.
+    pub extern_c: bool,
+    /// `repr_c`: Add `#[repr(C)]` to the structure.
+    pub repr_c: bool,
+}
+
+impl AttributeOptions {
+    pub fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("extern_c") {
+            self.extern_c = true;
+        } else if meta.path.is_ident("repr_c") {
+            self.repr_c = true;
+        } else {
+            return Err(meta.error("unsupported ffi_destruct option"));
+        }
+        Ok(())
+    }
+}
+
+pub fn impl_ffi_destruct_attribute(options: &AttributeOptions, input: &DeriveInput) -> TokenStream {
+    let name = &input.ident;
+    // The added `#[repr(C)]` satisfies `require_repr_c`
+    let destructor = if options.repr_c {
+        let mut input = input.clone();
+        input.attrs.push(syn::parse_quote!(#[repr(C)]));
+        impl_destruct_macro(&input)
+    } else {
+        impl_destruct_macro(input)
+    };
+
+    let extern_c = if options.extern_c {
+        let input: extern_c::Destructor = syn::parse_quote!(#name);
+        extern_c::impl_extern_c_destructor(&input)
+    } else {
+        TokenStream::new()
+    };
+
+    let repr = if options.repr_c {
+        quote! { #[repr(C)] }
+    } else {
+        TokenStream::new()
+    };
+
+    // Leave the structure intact, except for the helper attributes
+    let mut item = input.clone();
+    utils::strip_helper_attributes(&mut item.attrs);
+    match item.data {
+        Data::Struct(ref mut data) => {
+            for field in data.fields.iter_mut() {
+                utils::strip_helper_attributes(&mut field.attrs);
+            }
+        }
+        Data::Union(ref mut data) => {
+            for field in data.fields.named.iter_mut() {
+                utils::strip_helper_attributes(&mut field.attrs);
+            }
+        }
+        Data::Enum(_) => {}
+    }
+
+    quote! {
+        #repr
+        #item
+        #destructor
+        #extern_c
+    }
+}
+
+/// What the generated code of the fields does.
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    /// Free all the fields.
+    Free,
+    /// Free only the fields marked `#[owned]`.
+    FreeOwned,
+    /// Add the number of objects that would be freed to `count`.
+    Count,
+    /// Free and drop only the fields flagged in `set`.
+    FreeSet,
+    /// Free all the fields on the background thread of `#[destruct(defer)]`.
+    Defer,
+}
+
+impl Mode {
+    /// Whether every field is freed, which reports the invalid fields.
+    fn frees_all(self) -> bool {
+        matches!(self, Mode::Free | Mode::Defer)
+    }
+}
+
+/// The type holding the raw pointer of a field, if not a raw pointer itself.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Wrapper {
+    /// `NonNull<T>`, never null.
+    NonNull,
+    /// `Option<NonNull<T>>`, with `None` as null.
+    OptionalNonNull,
+    /// `[*mut T; N]`, each non-null slot is freed.
+    Slots,
+}
+
 /// Parsing fields and generating destructors for them.
-fn field_destructors(data: &Data) -> TokenStream {
+fn field_destructors(
+    ident: &Ident,
+    data: &Data,
+    attrs: &[utils::FieldAttributes],
+    options: &utils::ContainerAttributes,
+    mode: Mode,
+) -> TokenStream {
     match *data {
         Data::Struct(ref data) => match data.fields {
-            syn::Fields::Named(ref fields) => {
-                let recurse = fields.named.iter().map(|f| {
-                    let name = &f.ident;
-                    let attrs = &f.attrs;
-
-                    let nullable = utils::get_attribute(attrs, "nullable");
-                    let no_drop = utils::get_attribute(attrs, "no_drop");
-
-                    match f.ty {
-                        // Raw pointer destructor
-                        syn::Type::Ptr(ref ty) => {
-                            let destructor = destruct_type_ptr(name.as_ref().unwrap(), ty);
-                            if no_drop {
-                                TokenStream::new()
-                            } else if nullable {
-                                quote_spanned! { f.span() =>
-                                    if !self.#name.is_null() {
-                                        #destructor
-                                    }
-                                }
-                            } else {
-                                quote_spanned! { f.span() =>
-                                    #destructor
-                                }
-                            }
+            syn::Fields::Named(_) | syn::Fields::Unnamed(_) => {
+                let mut destructors = data
+                    .fields
+                    .iter()
+                    .zip(attrs)
+                    .enumerate()
+                    .map(|(index, (f, attrs))| {
+                        if attrs.union_active && mode.frees_all() {
+                            let message = "Union active attribute is only supported for unions";
+                            return (0, syn::Error::new_spanned(f, message).to_compile_error());
                         }
-                        // Other types don't require manual destructors
-                        _ => {
-                            if nullable {
-                                panic!("Nullable attribute is only supported for raw pointers");
+                        let order = attrs.drop_order;
+                        // `self.name` or `self.0` for tuple structures
+                        let name = match f.ident {
+                            Some(ref ident) => syn::Member::Named(ident.clone()),
+                            None => syn::Member::Unnamed(index.into()),
+                        };
+                        let destructor = field_destructor(ident, f, &name, attrs, options, mode);
+                        if mode != Mode::FreeSet {
+                            return (order, destructor);
+                        }
+                        // Unset fields are uninitialized, the others are dropped as well
+                        let destructor = quote! {
+                            if set[#index] {
+                                #destructor
+                                ::std::ptr::drop_in_place(&mut self.#name);
                             }
-                            if no_drop {
-                                panic!("No drop attribute is only supported for raw pointers");
+                        };
+                        (order, destructor)
+                    })
+                    .collect::<Vec<_>>();
+                // Lower orders first, the stable sort keeps the declaration order of ties
+                destructors.sort_by_key(|(order, _)| *order);
+                let destructors = destructors
+                    .into_iter()
+                    .map(|(_, destructor)| destructor)
+                    .filter(|destructor| !destructor.is_empty())
+                    .collect::<Vec<_>>();
+                // Free the other fields if one panics, then resume the first panic. Only in `drop`,
+                // the closures would hide the caller of `destruct_partial()` from `#[track_caller]`
+                if cfg!(feature = "panic-safe")
+                    && matches!(mode, Mode::Free | Mode::FreeSet)
+                    && !destructors.is_empty()
+                {
+                    return quote! {
+                        let mut panic: ::std::option::Option<
+                            ::std::boxed::Box<dyn ::std::any::Any + ::std::marker::Send>,
+                        > = ::std::option::Option::None;
+                        #(
+                            let result = ::std::panic::catch_unwind(
+                                ::std::panic::AssertUnwindSafe(|| {
+                                    #destructors
+                                })
+                            );
+                            if let ::std::result::Result::Err(payload) = result {
+                                panic.get_or_insert(payload);
                             }
-                            TokenStream::new() // Empty
+                        )*
+                        if let ::std::option::Option::Some(payload) = panic {
+                            ::std::panic::resume_unwind(payload);
                         }
-                    }
+                    };
+                }
+                quote! {
+                    #(#destructors)*
+                }
+            }
+            // Nothing to free, the `Drop` is empty
+            syn::Fields::Unit => TokenStream::new(),
+        },
+        // Only the field marked `#[union_active]` is live
+        Data::Union(ref data) => {
+            let active = data
+                .fields
+                .named
+                .iter()
+                .zip(attrs)
+                .filter(|(_, attrs)| attrs.union_active)
+                .collect::<Vec<_>>();
+            match active[..] {
+                [(f, attrs)] => {
+                    let name = syn::Member::Named(f.ident.clone().unwrap());
+                    field_destructor(ident, f, &name, attrs, options, mode)
+                }
+                _ if !mode.frees_all() => TokenStream::new(),
+                _ => {
+                    let message = "Exactly one union field must be marked `#[union_active]`";
+                    syn::Error::new_spanned(&data.fields, message).to_compile_error()
+                }
+            }
+        }
+        // Rejected by `impl_destruct_macro`
+        Data::Enum(_) => TokenStream::new(),
+    }
+}
+
+/// Generate the destructor of a field.
+fn field_destructor(
+    ident: &Ident,
+    f: &syn::Field,
+    name: &syn::Member,
+    attrs: &utils::FieldAttributes,
+    options: &utils::ContainerAttributes,
+    mode: Mode,
+) -> TokenStream {
+    let nullable_by_default = attrs.is_nullable(options);
+    let utils::FieldAttributes {
+        nullable,
+        ref sentinel,
+        non_null,
+        no_drop,
+        owned,
+        ref drop_if,
+        ref owned_if,
+        ref reclaim,
+        ref ptr_box,
+        ref drop_with,
+        ref array,
+        ref byte_string,
+        wide,
+        c_string,
+        flush_before_drop: ref flush,
+        ref pre_drop,
+        pre_free,
+        ref alias_of,
+        ref init_guard,
+        interned,
+        ref interned_registry,
+        ref free_if_callback,
+        cfg_not_miri_skip,
+        union_active: _,
+        drop_order: _,
+        ref erased,
+        ref attributes,
+        duplicates: _,
+    } = *attrs;
+    // The helper attributes of the field of the kind, in the order of the table
+    let of_kind = |kind: utils::Kind| {
+        utils::HELPER_ATTRIBUTES
+            .iter()
+            .filter(move |helper| {
+                helper.kinds.contains(&kind) && attributes.iter().any(|name| name == helper.name)
+            })
+            .map(|helper| helper.name)
+    };
+
+    // The children are freed by the iterative destructor
+    if options.iterative && f.ident == options.child {
+        return TokenStream::new();
+    }
+    if mode == Mode::FreeOwned && !owned {
+        return TokenStream::new();
+    }
+
+    // The raw pointer type of `NonNull<T>`
+    let non_null_ty;
+    let (ty, lock, wrapper) = match f.ty {
+        // Inline array of raw pointers, e.g. `[*mut T; N]`
+        syn::Type::Array(ref slots) if matches!(*slots.elem, syn::Type::Ptr(_)) => {
+            let syn::Type::Ptr(ref ty) = *slots.elem else {
+                unreachable!()
+            };
+            (ty, None, Some(Wrapper::Slots))
+        }
+        // Raw pointer destructor
+        syn::Type::Ptr(ref ty) => (ty, None, None),
+        // Raw pointer behind a lock, e.g. `Mutex<*mut T>`
+        ref ty if locked_pointer(ty).is_some() => {
+            let (lock, ty) = locked_pointer(ty).unwrap();
+            (ty, Some(lock), None)
+        }
+        // `NonNull<T>`, or `Option<NonNull<T>>` with `None` as null
+        ref ty if non_null_pointer(ty).is_some() => {
+            let (ty, optional) = non_null_pointer(ty).unwrap();
+            non_null_ty = ty;
+            let wrapper = if optional {
+                Wrapper::OptionalNonNull
+            } else {
+                Wrapper::NonNull
+            };
+            (&non_null_ty, None, Some(wrapper))
+        }
+        // Other types don't require manual destructors
+        _ => {
+            if let Some(attribute) = of_kind(utils::Kind::PointerOnly).next() {
+                // e.g. `Flush before drop` for `flush_before_drop`
+                let mut attribute = attribute.replace('_', " ");
+                attribute[..1].make_ascii_uppercase();
+                let message = format!("{} attribute is only supported for raw pointers", attribute);
+                return syn::Error::new_spanned(f, message).to_compile_error();
+            }
+            // Reported once, for the destructor itself
+            if options.strict && mode.frees_all() && !no_drop && !drops_itself(&f.ty) {
+                let message = "Field of unknown type in strict mode, mark it `#[no_drop]` if it \
+                               needs no manual freeing";
+                return syn::Error::new_spanned(&f.ty, message).to_compile_error();
+            }
+            return TokenStream::new(); // Empty
+        }
+    };
+    if nullable && non_null {
+        let message = "Nullable attribute cannot be combined with `non_null`";
+        return syn::Error::new_spanned(f, message).to_compile_error();
+    }
+    // Attributes silently ignored in favour of another, reported once for the destructor
+    if mode.frees_all() {
+        let conflict = if no_drop {
+            of_kind(utils::Kind::IgnoredByNoDrop)
+                .next()
+                .map(|other| ("no_drop", other))
+        } else {
+            // Only the first way of freeing the pointer applies. Wide strings take the length
+            // of `array`.
+            let mut used = of_kind(utils::Kind::FreeMethod)
+                .filter(|attribute| !(wide && *attribute == "array"));
+            used.next().zip(used.next())
+        };
+        if let Some((attribute, other)) = conflict {
+            let message = format!(
+                "`{}` attribute cannot be combined with `{}`",
+                attribute, other
+            );
+            return syn::Error::new_spanned(f, message).to_compile_error();
+        }
+    }
+    if no_drop {
+        return TokenStream::new();
+    }
+    // Optional pointers and the slots of arrays are always null-checked
+    let nullable =
+        nullable_by_default || matches!(wrapper, Some(Wrapper::OptionalNonNull | Wrapper::Slots));
+    if wrapper == Some(Wrapper::Slots) && (interned || alias_of.is_some()) {
+        let message = "Interned and alias attributes are not supported for pointer arrays";
+        return syn::Error::new_spanned(f, message).to_compile_error();
+    }
+
+    // The name of the field in messages
+    let label = match *name {
+        syn::Member::Named(ref ident) => ident.to_string(),
+        syn::Member::Unnamed(ref index) => index.index.to_string(),
+    };
+
+    // The pointer, copied out of the lock if any
+    let (prelude, ptr) = match lock {
+        Some(lock) => {
+            let guard = match (mode, lock.to_string().as_str()) {
+                (Mode::Count, "Mutex") => quote! { lock() },
+                (Mode::Count, _) => quote! { read() },
+                _ => quote! { get_mut() },
+            };
+            (
+                quote! {
+                    let ptr = *self.#name.#guard
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner);
+                },
+                quote! { ptr },
+            )
+        }
+        None => match wrapper {
+            Some(Wrapper::NonNull) => (TokenStream::new(), quote! { self.#name.as_ptr() }),
+            Some(Wrapper::OptionalNonNull) => (
+                TokenStream::new(),
+                quote! { self.#name.map_or(::std::ptr::null_mut(), ::std::ptr::NonNull::as_ptr) },
+            ),
+            // Bound for each slot by the loop
+            Some(Wrapper::Slots) => (TokenStream::new(), quote! { ptr }),
+            None => (TokenStream::new(), quote! { self.#name }),
+        },
+    };
+
+    // Interned strings are owned by their pool
+    if interned {
+        let Some(registry) = interned_registry else {
+            return TokenStream::new();
+        };
+        if !mode.frees_all() {
+            return TokenStream::new();
+        }
+        let message = format!("`{}` is not interned", label);
+        return quote_spanned! { f.span() =>
+            #prelude
+            debug_assert!((#registry)(#ptr as *const _), #message);
+        };
+    }
+
+    // Aliases are freed through the field they alias
+    if let Some(other) = alias_of {
+        if !mode.frees_all() {
+            return TokenStream::new();
+        }
+        let message = format!("`{}` is not an alias of `{}`", label, other);
+        return quote_spanned! { f.span() =>
+            #prelude
+            debug_assert!(
+                #ptr as *const () == self.#other as *const (),
+                #message
+            );
+        };
+    }
+    let trace = trace_free(ident, &label);
+    let record = record_free(&ptr, options);
+    let counter = count_free(ident);
+    let size = if mode.frees_all() {
+        size_free(
+            &ptr,
+            ty,
+            array,
+            byte_string,
+            wide,
+            c_string,
+            erased.is_some(),
+        )
+    } else {
+        TokenStream::new()
+    };
+    // The background thread only gets the pointer and the values read from the structure
+    let deferred = mode == Mode::Defer;
+    let (freed, freed_array, freed_string, drop_fn) = if deferred {
+        let deferred_len = |array: &utils::ArrayAttribute| utils::ArrayAttribute {
+            len: quote! { deferred_len },
+            require_drop: array.require_drop,
+            cap: array.cap.as_ref().map(|_| quote! { deferred_cap }),
+        };
+        (
+            quote! { ptr },
+            array.as_ref().map(deferred_len),
+            byte_string.as_ref().map(deferred_len),
+            quote! { deferred_drop_fn },
+        )
+    } else {
+        let drop_fn = erased.as_ref().map(|drop_fn| quote! { self.#drop_fn });
+        (
+            ptr.clone(),
+            array.clone(),
+            byte_string.clone(),
+            drop_fn.unwrap_or_default(),
+        )
+    };
+    let destructor = if let Some(ref function) = drop_with {
+        destruct_with(&freed, function)
+    } else if let Some(ref container) = reclaim {
+        destruct_reclaim(&freed, container)
+    } else if let Some(ref container) = ptr_box {
+        let elem = &ty.elem;
+        destruct_reclaim(&freed, &syn::parse_quote! { #container<#elem> })
+    } else if erased.is_some() {
+        destruct_erased(&freed, &drop_fn)
+    } else if let (Some(ref array), true) = (&freed_array, wide) {
+        // The length of the array counts the units, including the NUL
+        let unit = wide_unit(&label, ty);
+        let destructor = destruct_array(&label, &freed, ty, array);
+        quote! {
+            #unit
+            #destructor
+        }
+    } else if let Some(ref array) = freed_array {
+        destruct_array(&label, &freed, ty, array)
+    } else if let Some(ref string) = freed_string {
+        destruct_byte_string(&label, &freed, ty, string)
+    } else if wide {
+        destruct_wide_string(&label, &freed, ty)
+    } else if c_string {
+        destruct_c_string(&label, &freed, ty)
+    } else {
+        destruct_type_ptr(&freed, ty)
+    };
+    // Null out the field so a second drop sees nothing to free, `NonNull<T>` cannot be null
+    let destructor = if options.zero_on_drop && wrapper != Some(Wrapper::NonNull) {
+        let null = match (ty.mutability, wrapper) {
+            (_, Some(Wrapper::OptionalNonNull)) => quote! { ::std::option::Option::None },
+            (Some(_), _) => quote! { ::std::ptr::null_mut() },
+            (None, _) => quote! { ::std::ptr::null() },
+        };
+        let field = match (lock, wrapper) {
+            (Some(_), _) => quote! {
+                *self.#name.get_mut().unwrap_or_else(::std::sync::PoisonError::into_inner)
+            },
+            (None, Some(Wrapper::Slots)) => quote! { *slot },
+            (None, _) => quote! { self.#name },
+        };
+        quote! {
+            #destructor
+            #field = #null;
+        }
+    } else {
+        destructor
+    };
+    // Leak allocations Miri cannot model
+    let destructor = if cfg_not_miri_skip {
+        quote! {
+            #[cfg(not(miri))]
+            {
+                #destructor
+            }
+        }
+    } else {
+        destructor
+    };
+
+    // Conditions to check before freeing
+    let mut conditions = Vec::new();
+    if let Some(once) = init_guard {
+        conditions.push(quote! { self.#once.is_completed() });
+    }
+    if let Some(sentinel) = sentinel {
+        conditions.push(quote! { #ptr != (#sentinel) });
+    }
+    if nullable {
+        conditions.push(quote! { !#ptr.is_null() });
+    }
+    if let Some(condition) = drop_if {
+        conditions.push(quote! { (#condition) });
+    }
+    if let Some(callback) = free_if_callback {
+        conditions.push(quote! {
+            match self.#callback {
+                ::std::option::Option::Some(callback) => {
+                    callback(#ptr as *mut ::std::ffi::c_void)
+                }
+                ::std::option::Option::None => true,
+            }
+        });
+    }
+
+    // Statements to run right before freeing
+    let mut checks = Vec::new();
+    if let Some(owner) = owned_if {
+        conditions.push(quote! { self.#owner });
+        if !nullable {
+            let message = format!("`{}` is null while `{}` is set", label, owner);
+            checks.push(quote! {
+                debug_assert!(!#freed.is_null(), #message);
+            });
+        }
+    }
+
+    if let Some(method) = flush {
+        checks.push(quote! {
+            let _ = (&mut *#freed).#method();
+        });
+    }
+    if let Some(method) = pre_drop {
+        checks.push(quote! {
+            (*#freed).#method();
+        });
+    }
+    if pre_free {
+        checks.push(quote! {
+            (&mut *#freed).pre_free();
+        });
+    }
+
+    let body = match (mode, array) {
+        (Mode::Count, Some(utils::ArrayAttribute { len, .. })) => quote! {
+            #[allow(clippy::unnecessary_cast)]
+            let len = (#len) as usize;
+            count += len;
+        },
+        (Mode::Count, None) => quote! {
+            count += 1;
+        },
+        (Mode::Defer, _) => {
+            // Read on the dropping thread, the structure is gone once the job runs
+            let mut values = Vec::new();
+            if let Some(utils::ArrayAttribute {
+                ref len, ref cap, ..
+            }) = array.as_ref().or(byte_string.as_ref())
+            {
+                values.push(quote! {
+                    #[allow(clippy::unnecessary_cast)]
+                    let deferred_len = (#len) as usize;
                 });
+                if let Some(cap) = cap {
+                    values.push(quote! {
+                        #[allow(clippy::unnecessary_cast)]
+                        let deferred_cap = (#cap) as usize;
+                    });
+                }
+            }
+            if let Some(ref drop_fn) = erased {
+                values.push(quote! {
+                    let deferred_drop_fn = self.#drop_fn;
+                });
+            }
+            // The containers are moved to the background thread with the pointer
+            let container = match (&reclaim, &ptr_box) {
+                (Some(container), _) => Some(quote! { #container }),
+                (None, Some(container)) => {
+                    let elem = &ty.elem;
+                    Some(quote! { #container<#elem> })
+                }
+                (None, None) => None,
+            };
+            let container = container.map(|container| {
                 quote! {
-                    #(#recurse)*
+                    const _: fn() = || {
+                        fn assert_send<T: ?::std::marker::Sized + ::std::marker::Send>() {}
+                        assert_send::<#container>();
+                    };
                 }
+            });
+            quote! {
+                #trace
+                #record
+                #counter
+                #size
+                #container
+                #(#values)*
+                let ptr = Pointer(#ptr);
+                Self::defer_job(::std::boxed::Box::new(move || {
+                    let ptr = ptr.get();
+                    #(#checks)*
+                    #destructor
+                }));
             }
-            syn::Fields::Unnamed(_) => unimplemented!("Unnamed fields are not supported"),
-            syn::Fields::Unit => panic!("Unit structs cannot be destructed"),
+        }
+        _ => quote! {
+            #(#checks)*
+            #trace
+            #record
+            #counter
+            #size
+            #destructor
         },
-        _ => panic!("Destruct can only be derived for structs"),
+    };
+    let body = if conditions.is_empty() {
+        quote_spanned! { f.span() =>
+            #body
+        }
+    } else {
+        quote_spanned! { f.span() =>
+            if #(#conditions)&&* {
+                #body
+            }
+        }
+    };
+    // Free each slot of the array
+    let body = if wrapper == Some(Wrapper::Slots) {
+        let iter = if mode == Mode::Count {
+            quote! { iter }
+        } else {
+            quote! { iter_mut }
+        };
+        quote_spanned! { f.span() =>
+            for slot in self.#name.#iter() {
+                let ptr = *slot;
+                #body
+            }
+        }
+    } else {
+        body
+    };
+    if prelude.is_empty() {
+        body
+    } else {
+        quote_spanned! { f.span() =>
+            {
+                #prelude
+                #body
+            }
+        }
     }
 }
 
-/// Generate destructor for raw pointer types
-fn destruct_type_ptr(name: &Ident, ty: &syn::TypePtr) -> TokenStream {
-    /// Some variant of `c_char` type paths: `std::ffi:c_char`,`libc::c_char`, `std::os::raw::c_char`,`c_char`,
-    fn is_c_char(path: &str) -> bool {
-        path.contains("c_char")
+/// Generate `destruct_partial()` freeing only the fields marked `#[owned]`,
+/// if there are any.
+fn partial_destructor(
+    ident: &Ident,
+    generics: &syn::Generics,
+    data: &Data,
+    attrs: &[utils::FieldAttributes],
+    options: &utils::ContainerAttributes,
+) -> TokenStream {
+    let has_owned = matches!(data, Data::Struct(_)) && attrs.iter().any(|attrs| attrs.owned);
+    if !has_owned {
+        return TokenStream::new();
+    }
+
+    let destructors = field_destructors(ident, data, attrs, options, Mode::FreeOwned);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Free only the fields marked `#[owned]`, then the structure itself without dropping it.
+            ///
+            /// # Safety
+            /// `ptr` must be null or allocated by `Box`, with the `#[owned]` fields initialized.
+            /// The other fields are never read.
+            ///
+            /// Panics of the generated checks are reported at the caller.
+            #[track_caller]
+            pub unsafe fn destruct_partial(ptr: *mut Self) {
+                if ptr.is_null() {
+                    return;
+                }
+                (*ptr).destruct_owned_fields();
+                let _ = ::std::boxed::Box::from_raw(ptr as *mut ::std::mem::ManuallyDrop<Self>);
+            }
+
+            #[track_caller]
+            unsafe fn destruct_owned_fields(&mut self) {
+                #destructors
+            }
+        }
+    }
+}
+
+/// Generate `destruct()` running the body of `drop` with `#[destruct(manual)]`, which `drop` calls
+/// as well.
+fn manual_destructor(
+    ident: &Ident,
+    generics: &syn::Generics,
+    data: &Data,
+    drop_body: TokenStream,
+) -> TokenStream {
+    // The drop glue of the fields, skipped with `Drop` itself, in declaration order
+    let fields = match *data {
+        Data::Struct(ref data) => data.fields.members().collect(),
+        _ => Vec::new(),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        // The body of `drop`, with the same lint settings
+        #[allow(unused_unsafe)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Free the structure as dropping it would.
+            ///
+            /// Panics of the generated checks are reported at the caller.
+            #[track_caller]
+            pub fn destruct(self) {
+                let mut this = ::std::mem::ManuallyDrop::new(self);
+                this.destruct_manually();
+                unsafe {
+                    #(::std::ptr::drop_in_place(::std::ptr::addr_of_mut!(this.#fields));)*
+                }
+            }
+
+            #[track_caller]
+            fn destruct_manually(&mut self) {
+                #drop_body
+            }
+        }
+    }
+}
+
+/// Generate the builder of the structure with `#[destruct(builder)]`.
+///
+/// The builder tracks the fields set so far, and frees only those if dropped before `build()`.
+fn builder(
+    ident: &Ident,
+    generics: &syn::Generics,
+    vis: &syn::Visibility,
+    data: &Data,
+    attrs: &[utils::FieldAttributes],
+    options: &utils::ContainerAttributes,
+) -> TokenStream {
+    if !options.builder {
+        return TokenStream::new();
+    }
+    let Data::Struct(ref struct_data) = *data else {
+        return TokenStream::new();
+    };
+    let syn::Fields::Named(ref fields) = struct_data.fields else {
+        return syn::Error::new_spanned(&struct_data.fields, "Builder requires named fields")
+            .to_compile_error();
+    };
+
+    let builder = Ident::new(&format!("{}Builder", ident), ident.span());
+    let len = fields.named.len();
+    let setters = fields.named.iter().enumerate().map(|(index, f)| {
+        let name = &f.ident;
+        let ty = &f.ty;
+        let field = syn::ext::IdentExt::unraw(name.as_ref().unwrap());
+        let message = format!("`{}` is already set", field);
+        // Prefixed, so fields like `new` or `build` do not collide with the other methods
+        let setter = Ident::new(&format!("with_{}", field), field.span());
+        quote! {
+            /// Set the field, which must not be set yet.
+            ///
+            /// Pointers are owned like in the structure, and freed if the builder is dropped.
+            #[allow(clippy::not_unsafe_ptr_arg_deref)]
+            #vis fn #setter(&mut self, value: #ty) -> &mut Self {
+                assert!(!self.set[#index], #message);
+                unsafe {
+                    ::std::ptr::addr_of_mut!((*self.inner.as_mut_ptr()).#name).write(value);
+                }
+                self.set[#index] = true;
+                self
+            }
+        }
+    });
+    let destructors = field_destructors(ident, data, attrs, options, Mode::FreeSet);
+    let doc = format!(
+        "Builder of [`{}`], freeing the fields set so far if dropped before `build()`.",
+        ident
+    );
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #[doc = #doc]
+        #vis struct #builder #generics #where_clause {
+            inner: ::std::mem::MaybeUninit<#ident #ty_generics>,
+            set: [bool; #len],
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #vis fn new() -> Self {
+                #builder {
+                    inner: ::std::mem::MaybeUninit::uninit(),
+                    set: [false; #len],
+                }
+            }
+
+            #(#setters)*
+
+            /// Build the structure if every field is set, otherwise return the builder.
+            #vis fn build(self) -> ::std::result::Result<#ident #ty_generics, Self> {
+                if self.set.iter().all(|&set| set) {
+                    let this = ::std::mem::ManuallyDrop::new(self);
+                    ::std::result::Result::Ok(unsafe { this.inner.assume_init_read() })
+                } else {
+                    ::std::result::Result::Err(self)
+                }
+            }
+        }
+
+        impl #impl_generics ::std::default::Default for #builder #ty_generics #where_clause {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        #[allow(unused_unsafe)]
+        impl #impl_generics ::std::ops::Drop for #builder #ty_generics #where_clause {
+            fn drop(&mut self) {
+                unsafe { (*self.inner.as_mut_ptr()).destruct_set_fields(&self.set) };
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            unsafe fn destruct_set_fields(&mut self, set: &[bool; #len]) {
+                #destructors
+            }
+        }
+    }
+}
+
+/// Generate `live_gauge()` with the `gauge` feature.
+fn live_gauge(
+    ident: &Ident,
+    generics: &syn::Generics,
+    options: &utils::ContainerAttributes,
+) -> TokenStream {
+    if !cfg!(feature = "gauge") {
+        return TokenStream::new();
+    }
+
+    // Opted in types register in the `gauge` module of the crate root, failing if it is missing
+    let register = if options.gauge {
+        quote! {
+            static REGISTERED: ::std::sync::Once = ::std::sync::Once::new();
+            REGISTERED.call_once(|| {
+                crate::gauge::register(
+                    ::std::concat!(::std::module_path!(), "::", ::std::stringify!(#ident)),
+                    &GAUGE,
+                );
+            });
+        }
+    } else {
+        TokenStream::new()
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Gauge of the live objects: incremented on construction, decremented on drop.
+            pub fn live_gauge() -> &'static ::std::sync::atomic::AtomicUsize {
+                static GAUGE: ::std::sync::atomic::AtomicUsize =
+                    ::std::sync::atomic::AtomicUsize::new(0);
+                #register
+                &GAUGE
+            }
+        }
     }
+}
+
+/// Generate the `gauge` module of `gauge_registry!()`, the live gauges of every type by name.
+pub fn impl_gauge_registry() -> TokenStream {
+    quote! {
+        /// The live objects of every type of the crate by name, for monitoring.
+        pub mod gauge {
+            type Gauges = ::std::vec::Vec<(&'static str, &'static ::std::sync::atomic::AtomicUsize)>;
+
+            fn gauges() -> &'static ::std::sync::Mutex<Gauges> {
+                static GAUGES: ::std::sync::Mutex<Gauges> =
+                    ::std::sync::Mutex::new(::std::vec::Vec::new());
+                &GAUGES
+            }
+
+            /// Register the gauge of a type by its path, called by its `live_gauge()` on first use.
+            pub fn register(name: &'static str, gauge: &'static ::std::sync::atomic::AtomicUsize) {
+                gauges()
+                    .lock()
+                    .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                    .push((name, gauge));
+            }
+
+            /// The live objects of the type, `None` until its gauge is first used.
+            pub fn live(name: &str) -> ::std::option::Option<usize> {
+                gauges()
+                    .lock()
+                    .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                    .iter()
+                    .find(|(registered, _)| *registered == name)
+                    .map(|(_, gauge)| gauge.load(::std::sync::atomic::Ordering::Relaxed))
+            }
 
+            /// The live objects of every registered type, in the order of registration.
+            pub fn snapshot() -> ::std::vec::Vec<(&'static str, usize)> {
+                gauges()
+                    .lock()
+                    .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                    .iter()
+                    .map(|&(name, gauge)| (name, gauge.load(::std::sync::atomic::Ordering::Relaxed)))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Generate `into_ffi()` setting the consumed flag.
+fn into_ffi(
+    ident: &Ident,
+    generics: &syn::Generics,
+    vis: &syn::Visibility,
+    options: &utils::ContainerAttributes,
+) -> TokenStream {
+    let consumed = match options.consumed_flag {
+        Some(ref consumed) => consumed,
+        None => return TokenStream::new(),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Mark the ownership of the fields as transferred across FFI, so dropping frees nothing.
+            #vis fn into_ffi(&mut self) {
+                self.#consumed = true;
+            }
+        }
+    }
+}
+
+/// Implement the `Destructible` trait of `destructible!()` with `#[destruct(destructible)]`.
+fn destructible(
+    ident: &Ident,
+    generics: &syn::Generics,
+    options: &utils::ContainerAttributes,
+) -> TokenStream {
+    if !options.destructible {
+        return TokenStream::new();
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics Destructible for #ident #ty_generics #where_clause {}
+    }
+}
+
+/// Generate the `Destructible` trait implemented with `#[destruct(destructible)]`.
+pub fn impl_destructible_trait() -> TokenStream {
+    quote! {
+        /// Structures freed from a raw pointer, like the destructors of `extern_c_destructor!`.
+        pub trait Destructible: ::std::marker::Sized {
+            /// Free the structure allocated with `Box`, nothing if the pointer is null.
+            ///
+            /// # Safety
+            /// The pointer must be null or returned by `Box::into_raw`, and not freed yet.
+            unsafe fn free_raw(ptr: *mut Self) {
+                if ptr.is_null() {
+                    return;
+                }
+                let _ = ::std::boxed::Box::from_raw(ptr);
+            }
+        }
+    }
+}
+
+/// Generate the `fuzz` module of `fuzz_log!()`, the log of the addresses freed by every type.
+pub fn impl_fuzz_log_module() -> TokenStream {
+    quote! {
+        /// The addresses freed by the destructors of the `#[destruct(fuzz)]` types of the crate, for
+        /// fuzzing harnesses.
+        pub mod fuzz {
+            /// Addresses freed by the destructors, in order.
+            pub fn freed_addresses() -> &'static ::std::sync::Mutex<::std::vec::Vec<usize>> {
+                static FREED: ::std::sync::Mutex<::std::vec::Vec<usize>> =
+                    ::std::sync::Mutex::new(::std::vec::Vec::new());
+                &FREED
+            }
+
+            /// Record a freed address, called by the destructors before each free.
+            pub fn record_free(addr: usize) {
+                freed_addresses()
+                    .lock()
+                    .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                    .push(addr);
+            }
+        }
+    }
+}
+
+/// Generate `defer_job()` running the frees of `#[destruct(defer)]` on a background thread, and
+/// `flush_deferred()` waiting for them.
+///
+/// The destructor reads the fields on the dropping thread, and each job only gets the pointer and
+/// the values it needs. The pointees are asserted to be `Send`.
+fn deferred_destructor(
+    ident: &Ident,
+    data: &Data,
+    attrs: &[utils::FieldAttributes],
+    options: &utils::ContainerAttributes,
+) -> TokenStream {
+    if !options.defer {
+        return TokenStream::new();
+    }
+
+    // The owned pointees, moved to the worker thread
+    let pointees = match *data {
+        Data::Struct(ref data) => data
+            .fields
+            .iter()
+            .zip(attrs)
+            .filter_map(|(f, attrs)| {
+                if attrs.no_drop || attrs.interned || attrs.alias_of.is_some() {
+                    return None;
+                }
+                pointer_type(&f.ty).map(|ty| (*ty.elem).clone())
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    quote! {
+        const _: () = {
+            fn assert_send<T: ?::std::marker::Sized + ::std::marker::Send>() {}
+            fn assert_pointees_send() {
+                #(assert_send::<#pointees>();)*
+            }
+        };
+
+        impl #ident {
+            /// Run the job on the background thread of the type, spawned on first use.
+            fn defer_job(job: ::std::boxed::Box<dyn ::std::ops::FnOnce() + ::std::marker::Send>) {
+                type Job = ::std::boxed::Box<dyn ::std::ops::FnOnce() + ::std::marker::Send>;
+                static QUEUE: ::std::sync::OnceLock<::std::sync::mpsc::Sender<Job>> =
+                    ::std::sync::OnceLock::new();
+
+                let queue = QUEUE.get_or_init(|| {
+                    let (sender, receiver) = ::std::sync::mpsc::channel::<Job>();
+                    ::std::thread::spawn(move || {
+                        for job in receiver {
+                            job();
+                        }
+                    });
+                    sender
+                });
+                // Free inline if the worker thread is gone
+                if let ::std::result::Result::Err(err) = queue.send(job) {
+                    (err.0)();
+                }
+            }
+
+            /// Wait until the background thread has freed the fields of every object of the type
+            /// dropped so far, e.g. before the process exits, which would lose the pending frees.
+            ///
+            /// It must not be called by the frees themselves, e.g. in a `#[pre_drop]` method,
+            /// which would wait for itself.
+            pub fn flush_deferred() {
+                let (done, finished) = ::std::sync::mpsc::channel::<()>();
+                Self::defer_job(::std::boxed::Box::new(move || {
+                    let _ = done.send(());
+                }));
+                let _ = finished.recv();
+            }
+        }
+    }
+}
+
+/// Generate the `DESTRUCT_SCHEMA` JSON describing the ownership of the fields with the
+/// `schema` feature.
+///
+/// ```json
+/// {"type":"Structure","fields":[{"name":"data","free":"array","nullable":true,"len":"self.len"}]}
+/// ```
+/// See the `schema` feature of the derive for the values of `free`.
+fn ownership_schema(
+    ident: &Ident,
+    generics: &syn::Generics,
+    data: &Data,
+    attrs: &[utils::FieldAttributes],
+    options: &utils::ContainerAttributes,
+) -> TokenStream {
+    if !cfg!(feature = "schema") {
+        return TokenStream::new();
+    }
+
+    /// Escape a string for JSON.
+    fn escape(value: &str) -> String {
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+
+    let Data::Struct(ref data) = *data else {
+        return TokenStream::new();
+    };
+    let fields = data
+        .fields
+        .iter()
+        .zip(attrs)
+        .enumerate()
+        .map(|(i, (f, attrs))| {
+            let name = f
+                .ident
+                .as_ref()
+                .map_or_else(|| i.to_string(), ToString::to_string);
+            let ty = pointer_type(&f.ty);
+            let ty = ty.as_deref();
+            let mut len = None;
+            let mut cap = None;
+            let free = match ty {
+                None => "none",
+                Some(_) if attrs.no_drop => "no_drop",
+                Some(_) if attrs.interned => "interned",
+                Some(_) if attrs.alias_of.is_some() => "alias",
+                Some(_) if options.iterative && f.ident == options.child => "children",
+                Some(_) if attrs.drop_with.is_some() => "drop_with",
+                Some(_) if attrs.reclaim.is_some() || attrs.ptr_box.is_some() => "reclaim",
+                Some(_) if attrs.erased.is_some() => "erased",
+                Some(_) if attrs.array.is_some() => {
+                    len = attrs.array.as_ref().map(|array| array.len.to_string());
+                    cap = attrs
+                        .array
+                        .as_ref()
+                        .and_then(|array| array.cap.as_ref())
+                        .map(ToString::to_string);
+                    "array"
+                }
+                Some(_) if attrs.byte_string.is_some() => {
+                    len = attrs
+                        .byte_string
+                        .as_ref()
+                        .map(|string| string.len.to_string());
+                    "byte_string"
+                }
+                Some(_) if attrs.wide => "wide_string",
+                Some(ty) if attrs.c_string || is_c_char_ptr(ty) => "c_string",
+                Some(_) => "box",
+            };
+
+            let mut json = format!("{{\"name\":\"{}\",\"free\":\"{}\"", name, free);
+            if ty.is_some() {
+                let optional = matches!(non_null_pointer(&f.ty), Some((_, true)))
+                    || matches!(f.ty, syn::Type::Array(_));
+                let nullable = attrs.is_nullable(options) || optional;
+                json.push_str(&format!(",\"nullable\":{}", nullable));
+            }
+            if let Some(len) = len {
+                json.push_str(&format!(",\"len\":\"{}\"", escape(&len)));
+            }
+            if let Some(cap) = cap {
+                json.push_str(&format!(",\"cap\":\"{}\"", escape(&cap)));
+            }
+            json.push('}');
+            json
+        });
+    let schema = format!(
+        "{{\"type\":\"{}\",\"fields\":[{}]}}",
+        ident,
+        fields.collect::<Vec<_>>().join(",")
+    );
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// JSON description of the ownership of the fields, for tooling.
+            pub const DESTRUCT_SCHEMA: &'static str = #schema;
+        }
+    }
+}
+
+/// Generate `destruct_count()` with `#[destruct(report)]`.
+fn report_count(
+    ident: &Ident,
+    generics: &syn::Generics,
+    data: &Data,
+    attrs: &[utils::FieldAttributes],
+    options: &utils::ContainerAttributes,
+) -> TokenStream {
+    if !options.report {
+        return TokenStream::new();
+    }
+
+    let counts = field_destructors(ident, data, attrs, options, Mode::Count);
+    // Reading the active field of a union is unsafe, it is live by contract
+    let counts = if matches!(data, Data::Union(_)) {
+        quote! {
+            unsafe {
+                #counts
+            }
+        }
+    } else {
+        counts
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Number of objects the destructor frees for the fields: one for each pointer,
+            /// and the number of elements for arrays.
+            #[allow(unused_mut)]
+            pub fn destruct_count(&self) -> usize {
+                let mut count = 0;
+                #counts
+                count
+            }
+        }
+    }
+}
+
+/// Generate the call to the release function of the vtable.
+fn release_destructor(options: &utils::ContainerAttributes) -> TokenStream {
+    match options.release_via {
+        Some((ref vtable, ref release)) => quote! {
+            let vtable = #vtable;
+            if !vtable.is_null() {
+                ((*vtable).#release)(self as *mut Self as *mut ::std::ffi::c_void);
+            }
+        },
+        None => TokenStream::new(),
+    }
+}
+
+/// Generate the iterative destructor for the tree of descendants.
+///
+/// The children of every node are detached and pushed onto a work-stack before the node
+/// itself is freed, so the nested `drop` calls never recurse.
+fn iterative_destructor(options: &utils::ContainerAttributes) -> TokenStream {
+    let (Some(child), Some(len)) = (&options.child, &options.len) else {
+        return TokenStream::new();
+    };
+    if !options.iterative {
+        return TokenStream::new();
+    }
+
+    quote! {
+        let mut stack: ::std::vec::Vec<*mut Self> = ::std::vec::Vec::new();
+        let children = ::std::mem::replace(&mut self.#child, ::std::ptr::null_mut());
+        let len = ::std::mem::replace(&mut self.#len, 0) as usize;
+        if !children.is_null() {
+            stack.extend(::std::vec::Vec::from_raw_parts(children, len, len));
+        }
+        while let Some(node) = stack.pop() {
+            if node.is_null() {
+                continue;
+            }
+            let children = ::std::mem::replace(&mut (*node).#child, ::std::ptr::null_mut());
+            let len = ::std::mem::replace(&mut (*node).#len, 0) as usize;
+            if !children.is_null() {
+                stack.extend(::std::vec::Vec::from_raw_parts(children, len, len));
+            }
+            let _ = ::std::boxed::Box::from_raw(node);
+        }
+    }
+}
+
+/// Generate a warning for deriving an empty `Drop`, which prevents the structure from being `Copy`.
+///
+/// There is no stable way to emit warnings from a proc macro, so a deprecated item is used.
+fn pointerless_warning(ident: &Ident) -> TokenStream {
+    let note = format!(
+        "`{}` has no pointer fields, the derived `Drop` is empty and prevents `Copy`; \
+         use `#[destruct(pod_ok)]` to skip generating it",
+        ident
+    );
+    quote_spanned! { ident.span() =>
+        const _: () = {
+            #[deprecated(note = #note)]
+            const POINTERLESS_DESTRUCT: () = ();
+            POINTERLESS_DESTRUCT
+        };
+    }
+}
+
+/// Generate a warning for each helper attribute repeated on a field, pointing at the repetition.
+fn duplicate_warnings(attrs: &[utils::FieldAttributes]) -> TokenStream {
+    let warnings = attrs.iter().flat_map(|attrs| {
+        attrs.duplicates.iter().map(|attribute| {
+            let note = format!(
+                "duplicate `#[{}]` attribute, only the first one applies",
+                attribute
+            );
+            quote_spanned! { attribute.span() =>
+                const _: () = {
+                    #[deprecated(note = #note)]
+                    const DUPLICATE_ATTRIBUTE: () = ();
+                    DUPLICATE_ATTRIBUTE
+                };
+            }
+        })
+    });
+    quote! {
+        #(#warnings)*
+    }
+}
+
+/// Generate the trace messages emitted before freeing a field.
+///
+/// Only present with the `defmt` or `trace` features, otherwise empty.
+fn trace_free(ident: &Ident, field: &str) -> TokenStream {
+    let message = format!("destruct: {}::{}", ident, field);
+    let mut trace = TokenStream::new();
+    if cfg!(feature = "defmt") {
+        trace.extend(quote! {
+            ::defmt::trace!(#message);
+        });
+    }
+    if cfg!(feature = "trace") {
+        trace.extend(quote! {
+            ::std::eprintln!(#message);
+        });
+    }
+    trace
+}
+
+/// Generate the counter of freed fields, labeled with the type of the structure.
+///
+/// Only present with the `metrics` feature, otherwise empty.
+fn count_free(ident: &Ident) -> TokenStream {
+    if cfg!(feature = "metrics") {
+        let name = ident.to_string();
+        quote! {
+            ::metrics::counter!("ffi_destruct_freed", "type" => #name).increment(1);
+        }
+    } else {
+        TokenStream::new()
+    }
+}
+
+/// Generate the addition of the freed bytes to `bytes`, from the type and length of the field.
+///
+/// Only present with the `size-log` feature, otherwise empty. Type-erased pointees have no known
+/// size and are not counted.
+fn size_free(
+    ptr: &TokenStream,
+    ty: &syn::TypePtr,
+    array: &Option<utils::ArrayAttribute>,
+    byte_string: &Option<utils::ArrayAttribute>,
+    wide: bool,
+    c_string: bool,
+    erased: bool,
+) -> TokenStream {
+    if !cfg!(feature = "size-log") || erased {
+        return TokenStream::new();
+    }
+    let elem = &ty.elem;
+    let size = if let Some(utils::ArrayAttribute {
+        ref len, ref cap, ..
+    }) = *array
+    {
+        let cap = cap.as_ref().unwrap_or(len);
+        quote! { (#cap) as usize * ::std::mem::size_of::<#elem>() }
+    } else if let Some(utils::ArrayAttribute { ref len, .. }) = *byte_string {
+        quote! { (#len) as usize }
+    } else if wide {
+        let len = wide_len(quote! { #ptr as *const u16 });
+        quote! { (#len + 1) * 2 }
+    } else if c_string || is_c_char_ptr(ty) {
+        quote! {
+            ::std::ffi::CStr::from_ptr(#ptr as *const ::std::ffi::c_char)
+                .to_bytes_with_nul()
+                .len()
+        }
+    } else {
+        quote! { ::std::mem::size_of_val(&*#ptr) }
+    };
+    quote! {
+        #[allow(clippy::unnecessary_cast)]
+        {
+            bytes += #size;
+        }
+    }
+}
+
+/// Generate the record of the freed address in the `fuzz` module of the crate root.
+///
+/// Only present with the `fuzz` feature for `#[destruct(fuzz)]`, otherwise empty.
+fn record_free(ptr: &TokenStream, options: &utils::ContainerAttributes) -> TokenStream {
+    if cfg!(feature = "fuzz") && options.fuzz {
+        quote! {
+            crate::fuzz::record_free(#ptr as *const () as usize);
+        }
+    } else {
+        TokenStream::new()
+    }
+}
+
+/// Generate destructor calling the custom free function with the pointer, e.g. `libc::free`.
+fn destruct_with(ptr: &TokenStream, function: &syn::Path) -> TokenStream {
+    quote! {
+        #function(#ptr as _);
+    }
+}
+
+/// Generate destructor reclaiming the pointer into a smart pointer container,
+/// e.g. `Rc<RefCell<T>>` for a pointer from `Rc::into_raw`.
+///
+/// The container's `from_raw` is checked by the compiler, so wrappers of other crates
+/// (e.g. `abi_stable::std_types::RBox<T>`) are supported as well.
+fn destruct_reclaim(ptr: &TokenStream, container: &syn::Type) -> TokenStream {
+    if !matches!(container, syn::Type::Path(_)) {
+        let message = "Reclaim type must be a container with a `from_raw` like `Rc<T>`";
+        return syn::Error::new_spanned(container, message).to_compile_error();
+    }
+
+    quote! {
+        let _ = <#container>::from_raw(#ptr as _);
+    }
+}
+
+/// Generate destructor for a type-erased pointer, calling the drop function stored in the
+/// named field, e.g. an `unsafe fn(*mut ())`.
+fn destruct_erased(ptr: &TokenStream, drop_fn: &TokenStream) -> TokenStream {
+    quote! {
+        #[allow(clippy::unnecessary_cast)]
+        (#drop_fn)(#ptr as *mut ());
+    }
+}
+
+/// Generate destructor for a byte string of `len` bytes without NUL terminator,
+/// allocated as a `Vec<u8>` with equal length and capacity.
+fn destruct_byte_string(
+    name: &str,
+    ptr: &TokenStream,
+    ty: &syn::TypePtr,
+    string: &utils::ArrayAttribute,
+) -> TokenStream {
+    let elem = &ty.elem;
+    let len = &string.len;
+    let message = format!(
+        "The elements of `{}` must be bytes, e.g. `u8` or `c_char`",
+        name
+    );
+
+    quote! {
+        const { assert!(::std::mem::size_of::<#elem>() == 1, #message) };
+        #[allow(clippy::unnecessary_cast)]
+        let len = (#len) as usize;
+        #[allow(clippy::unnecessary_cast)]
+        let _ = ::std::vec::Vec::from_raw_parts(#ptr as *mut u8, len, len);
+    }
+}
+
+/// Generate destructor for a C string of a byte-sized element type other than `c_char`.
+fn destruct_c_string(name: &str, ptr: &TokenStream, ty: &syn::TypePtr) -> TokenStream {
+    let elem = &ty.elem;
+    let message = format!(
+        "The elements of `{}` must be bytes, e.g. `i8` or `u8`",
+        name
+    );
+
+    quote! {
+        const { assert!(::std::mem::size_of::<#elem>() == 1, #message) };
+        let _ = ::std::ffi::CString::from_raw(#ptr as *mut ::std::ffi::c_char);
+    }
+}
+
+/// Generate destructor for a NUL-terminated wide string, allocated as a `Vec<u16>` (or boxed
+/// slice) including the NUL with equal length and capacity.
+fn destruct_wide_string(name: &str, ptr: &TokenStream, ty: &syn::TypePtr) -> TokenStream {
+    let unit = wide_unit(name, ty);
+    let len = wide_len(quote! { ptr });
+
+    quote! {
+        #unit
+        #[allow(clippy::unnecessary_cast)]
+        let ptr = #ptr as *mut u16;
+        let len = #len + 1;
+        let _ = ::std::vec::Vec::from_raw_parts(ptr, len, len);
+    }
+}
+
+/// Generate the assertion that the elements of a wide string are 16-bit units.
+fn wide_unit(name: &str, ty: &syn::TypePtr) -> TokenStream {
+    let elem = &ty.elem;
+    let message = format!(
+        "The elements of `{}` must be 16-bit units, e.g. `u16` or `wchar_t` on Windows",
+        name
+    );
+
+    quote! {
+        const { assert!(::std::mem::size_of::<#elem>() == 2, #message) };
+    }
+}
+
+/// Generate the number of units of a wide string before the NUL.
+fn wide_len(ptr: TokenStream) -> TokenStream {
+    quote! {
+        {
+            let units = #ptr;
+            let mut len = 0;
+            while *units.add(len) != 0 {
+                len += 1;
+            }
+            len
+        }
+    }
+}
+
+/// Generate destructor for a pointer to an array of `len` elements, allocated as a `Vec`
+/// (or boxed slice) with equal length and capacity, or the capacity `cap` of `#[vec(...)]`.
+fn destruct_array(
+    name: &str,
+    ptr: &TokenStream,
+    ty: &syn::TypePtr,
+    array: &utils::ArrayAttribute,
+) -> TokenStream {
+    let elem = &ty.elem;
+    let len = &array.len;
+    let ptr = if ty.mutability.is_some() {
+        ptr.clone()
+    } else {
+        quote! { #ptr as *mut #elem }
+    };
+
+    // Elements owning pointers must free them on drop, e.g. with `Destruct`
+    let require_drop = if array.require_drop {
+        let message = format!(
+            "The elements of `{}` must implement `Drop`, e.g. derive `Destruct`",
+            name
+        );
+        quote! {
+            const { assert!(::std::mem::needs_drop::<#elem>(), #message) };
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    // Arrays of pointers free each non-null element, e.g. `*mut *mut c_char`
+    let free = match **elem {
+        syn::Type::Ptr(ref inner) => {
+            let free_inner = destruct_type_ptr(&quote! { inner }, inner);
+            quote! {
+                for inner in ::std::vec::Vec::from_raw_parts(ptr, len, cap) {
+                    if !inner.is_null() {
+                        #free_inner
+                    }
+                }
+            }
+        }
+        _ => quote! {
+            let _ = ::std::vec::Vec::from_raw_parts(ptr, len, cap);
+        },
+    };
+
+    // The exact layout of the leaked `Vec`, deallocating with another capacity is undefined
+    let cap = match array.cap {
+        Some(ref cap) => {
+            let message = format!("`{}` has a length of {{}} above its capacity {{}}", name);
+            quote! {
+                #[allow(clippy::unnecessary_cast)]
+                let cap = (#cap) as usize;
+                debug_assert!(len <= cap, #message, len, cap);
+            }
+        }
+        None => quote! {
+            let cap = len;
+        },
+    };
+
+    // C APIs may return null for empty arrays
+    let message = format!("`{}` is null with a length of {{}}", name);
+    quote! {
+        #require_drop
+        #[allow(clippy::unnecessary_cast)]
+        let len = (#len) as usize;
+        let ptr = #ptr;
+        if ptr.is_null() {
+            debug_assert!(len == 0, #message, len);
+        } else {
+            #cap
+            #free
+        }
+    }
+}
+
+/// Get the raw pointer type behind a `Mutex` or `RwLock`, with the name of the lock.
+fn locked_pointer(ty: &syn::Type) -> Option<(&Ident, &syn::TypePtr)> {
+    let syn::Type::Path(ref path) = *ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Mutex" && segment.ident != "RwLock" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(ref args) = segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(syn::GenericArgument::Type(syn::Type::Ptr(ref ptr))) if args.args.len() == 1 => {
+            Some((&segment.ident, ptr))
+        }
+        _ => None,
+    }
+}
+
+/// Get the raw pointer type of `NonNull<T>` or `Option<NonNull<T>>`, and whether it is optional.
+fn non_null_pointer(ty: &syn::Type) -> Option<(syn::TypePtr, bool)> {
+    // The single type argument of the last segment, if it has the name
+    fn argument<'a>(ty: &'a syn::Type, name: &str) -> Option<&'a syn::Type> {
+        let syn::Type::Path(ref path) = *ty else {
+            return None;
+        };
+        let segment = path.path.segments.last()?;
+        if segment.ident != name {
+            return None;
+        }
+        let syn::PathArguments::AngleBracketed(ref args) = segment.arguments else {
+            return None;
+        };
+        match args.args.first() {
+            Some(syn::GenericArgument::Type(ref ty)) if args.args.len() == 1 => Some(ty),
+            _ => None,
+        }
+    }
+
+    let (elem, optional) = match argument(ty, "Option") {
+        Some(inner) => (argument(inner, "NonNull")?, true),
+        None => (argument(ty, "NonNull")?, false),
+    };
+    Some((syn::parse_quote! { *mut #elem }, optional))
+}
+
+/// Get the raw pointer type of a pointer field: a raw pointer, behind a lock, `NonNull`, or the
+/// element of an array of raw pointers.
+fn pointer_type(ty: &syn::Type) -> Option<Cow<'_, syn::TypePtr>> {
+    match *ty {
+        syn::Type::Ptr(ref ty) => Some(Cow::Borrowed(ty)),
+        syn::Type::Array(ref slots) => match *slots.elem {
+            syn::Type::Ptr(ref ty) => Some(Cow::Borrowed(ty)),
+            _ => None,
+        },
+        ref ty => match locked_pointer(ty) {
+            Some((_, ty)) => Some(Cow::Borrowed(ty)),
+            None => non_null_pointer(ty).map(|(ty, _)| Cow::Owned(ty)),
+        },
+    }
+}
+
+/// Check if the type is known to free itself without hiding raw pointers, for strict mode:
+/// primitives, references, function pointers, and the owned types of `std` wrapping them.
+fn drops_itself(ty: &syn::Type) -> bool {
+    const OWNED: &[&str] = &[
+        "bool",
+        "char",
+        "str",
+        "u8",
+        "u16",
+        "u32",
+        "u64",
+        "u128",
+        "usize",
+        "i8",
+        "i16",
+        "i32",
+        "i64",
+        "i128",
+        "isize",
+        "f32",
+        "f64",
+        "String",
+        "CString",
+        "OsString",
+        "PathBuf",
+        "Box",
+        "Vec",
+        "VecDeque",
+        "HashMap",
+        "HashSet",
+        "BTreeMap",
+        "BTreeSet",
+        "Option",
+        "Result",
+        "Rc",
+        "Arc",
+        "Weak",
+        "Cell",
+        "RefCell",
+        "Mutex",
+        "RwLock",
+        "Once",
+        "OnceCell",
+        "OnceLock",
+        "Sender",
+        "SyncSender",
+        "PhantomPinned",
+        "Duration",
+    ];
+
+    match *ty {
+        syn::Type::Reference(_) | syn::Type::BareFn(_) | syn::Type::Never(_) => true,
+        syn::Type::Array(ref array) => drops_itself(&array.elem),
+        syn::Type::Slice(ref slice) => drops_itself(&slice.elem),
+        syn::Type::Tuple(ref tuple) => tuple.elems.iter().all(drops_itself),
+        syn::Type::Paren(ref paren) => drops_itself(&paren.elem),
+        syn::Type::Group(ref group) => drops_itself(&group.elem),
+        syn::Type::Path(ref path) if path.qself.is_none() => {
+            let Some(segment) = path.path.segments.last() else {
+                return false;
+            };
+            let name = segment.ident.to_string();
+            // Owns nothing whatever its type argument, e.g. `PhantomData<*const u8>`
+            if name == "PhantomData" {
+                return true;
+            }
+            // C types, atomics and non-zero integers, e.g. `c_int`, `AtomicUsize`, `NonZeroU32`
+            let known = OWNED.contains(&name.as_str())
+                || name.starts_with("c_")
+                || name.starts_with("Atomic")
+                || name.starts_with("NonZero");
+            // The type arguments must free themselves too, e.g. not `Option<*mut T>`
+            known
+                && match segment.arguments {
+                    syn::PathArguments::AngleBracketed(ref args) => {
+                        args.args.iter().all(|arg| match arg {
+                            syn::GenericArgument::Type(ty) => drops_itself(ty),
+                            _ => true,
+                        })
+                    }
+                    _ => true,
+                }
+        }
+        _ => false,
+    }
+}
+
+/// Some variant of `c_char` type paths: `std::ffi:c_char`,`libc::c_char`, `std::os::raw::c_char`,`c_char`,
+/// matched by the last segment of the path.
+fn is_c_char(path: &syn::Path) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == "c_char")
+}
+
+/// Check if the layout is fixed by `#[repr(C)]` or `#[repr(transparent)]`, e.g. `#[repr(C, packed)]`.
+fn has_repr_c(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .any(|attr| {
+            let mut found = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                found |= meta.path.is_ident("C") || meta.path.is_ident("transparent");
+                // Skip the arguments of the other representations, e.g. `align(8)`
+                if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            });
+            found
+        })
+}
+
+/// Some variant of `c_void` type paths: `std::ffi::c_void`, `libc::c_void`, `c_void`,
+/// matched by the last segment of the path.
+fn is_c_void(path: &syn::Path) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == "c_void")
+}
+
+/// Check if the pointer is a C string, freed with `CString`.
+fn is_c_char_ptr(ty: &syn::TypePtr) -> bool {
+    matches!(*ty.elem, syn::Type::Path(ref path) if is_c_char(&path.path))
+}
+
+/// Generate destructor for raw pointer types
+fn destruct_type_ptr(ptr: &TokenStream, ty: &syn::TypePtr) -> TokenStream {
     match *ty.elem {
         syn::Type::Path(ref path) => {
-            let ts = path.path.to_token_stream();
-            let path_string = ts.to_string();
-            if is_c_char(&path_string) {
+            let ts = &path.path;
+            if is_c_void(ts) {
+                // Opaque pointee, only its allocator knows how to free it
+                let message = "`c_void` pointers cannot be freed with `Box`, \
+                               free them with `#[drop_with = \"...\"]`";
+                syn::Error::new_spanned(ty, message).to_compile_error()
+            } else if is_c_char(ts) && ty.mutability.is_some() {
                 // Drop c-string
                 quote_spanned! { ty.span()=>
-                    let _ = ::std::ffi::CString::from_raw(self.#name as *mut ::std::ffi::c_char);
+                    let _ = ::std::ffi::CString::from_raw(#ptr);
                 }
-            } else {
+            } else if is_c_char(ts) {
+                // Drop c-string, which is `*const`
+                quote_spanned! { ty.span()=>
+                    let _ = ::std::ffi::CString::from_raw(#ptr as *mut ::std::ffi::c_char);
+                }
+            } else if ty.mutability.is_some() {
                 // Drop other raw pointer
                 quote_spanned! { ty.span()=>
-                    let _ = ::std::boxed::Box::from_raw(self.#name as *mut #ts);
+                    let _ = ::std::boxed::Box::from_raw(#ptr);
+                }
+            } else {
+                // Drop other raw pointer, which is `*const`
+                quote_spanned! { ty.span()=>
+                    let _ = ::std::boxed::Box::from_raw(#ptr as *mut #ts);
+                }
+            }
+        }
+        // Drop the inner pointee, then the outer `Box<*mut T>`, e.g. `*mut *mut c_char`
+        syn::Type::Ptr(ref inner) => {
+            let elem = &ty.elem;
+            let outer = if ty.mutability.is_some() {
+                ptr.clone()
+            } else {
+                quote! { #ptr as *mut #elem }
+            };
+            let free_inner = destruct_type_ptr(&quote! { inner }, inner);
+            quote_spanned! { ty.span()=>
+                let inner = *::std::boxed::Box::from_raw(#outer);
+                if !inner.is_null() {
+                    #free_inner
+                }
+            }
+        }
+        // Drop unsized pointee through the fat pointer, e.g. `*mut [u8]` or `*mut dyn Trait`
+        syn::Type::Slice(_) | syn::Type::TraitObject(_) => {
+            let elem = &ty.elem;
+            if ty.mutability.is_some() {
+                quote_spanned! { ty.span()=>
+                    let _ = ::std::boxed::Box::from_raw(#ptr);
+                }
+            } else {
+                quote_spanned! { ty.span()=>
+                    let _ = ::std::boxed::Box::from_raw(#ptr as *mut #elem);
                 }
             }
         }
-        _ => panic!("Only single level raw pointers are supported"),
+        _ => syn::Error::new_spanned(ty, "Only single level raw pointers are supported")
+            .to_compile_error(),
     }
 }
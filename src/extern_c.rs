@@ -0,0 +1,874 @@
+use super::*;
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::Token;
+
+/// Arguments of the [`extern_c_destructor!`](crate::extern_c_destructor) macro.
+pub struct Destructor {
+    /// The types to generate a destructor for, each with the same options.
+    types: Vec<syn::Type>,
+    /// `name = "..."`: The function name, used verbatim.
+    name: Option<syn::LitStr>,
+    /// `abi = "..."`: The ABI of the function, `"C"` by default.
+    abi: Option<syn::LitStr>,
+    /// `cfg = "..."`: The configuration predicate the function is generated under.
+    cfg: Option<TokenStream>,
+    /// `abi_version = N`: The version the caller must pass to free the structure.
+    abi_version: Option<syn::LitInt>,
+    /// `doc_hidden`: Hide the function from rustdoc.
+    doc_hidden: bool,
+    /// `case = "..."`: The case of the type name in the function name, `None` to preserve it.
+    case: Option<Case>,
+    /// `prefix = "..."`: The prefix of the function name instead of `destruct_`.
+    prefix: Option<syn::LitStr>,
+    /// `array`: Free an array of `n` structures instead.
+    bulk: bool,
+    /// `array(...)`: The externally-sized array field.
+    array: Option<ArrayArgument>,
+    /// `report(out = "...")`: The out-parameter receiving the number of freed objects.
+    report: Option<Ident>,
+    /// `result`: Return a `DestructResult`, catching panics.
+    result: bool,
+    /// `returns_bool`: Return whether a structure was freed.
+    returns_bool: bool,
+    /// `checked`: Return a C status code, `0` if a structure was freed.
+    checked: bool,
+}
+
+/// The return values of a destructor reporting its outcome.
+struct Status {
+    /// The return type of the function.
+    ty: TokenStream,
+    /// The structure was freed.
+    freed: TokenStream,
+    /// The pointer was null.
+    null: TokenStream,
+    /// The ABI version did not match.
+    mismatch: TokenStream,
+}
+
+/// The ABIs accepted by the `abi` option. Target-specific ones only compile on their targets.
+const ABIS: &[&str] = &[
+    "C",
+    "C-unwind",
+    "system",
+    "system-unwind",
+    "cdecl",
+    "stdcall",
+    "fastcall",
+    "win64",
+    "sysv64",
+    "aapcs",
+    "efiapi",
+];
+
+/// An array field whose length is passed by the caller of the destructor.
+struct ArrayArgument {
+    /// `field = "..."`: The pointer field of the array.
+    field: Ident,
+    /// `elem = "..."`: The element type of the array.
+    elem: syn::Type,
+}
+
+/// The options of [`Destructor`], telling them apart from the types listed before.
+const DESTRUCTOR_OPTIONS: &[&str] = &[
+    "name",
+    "abi",
+    "cfg",
+    "abi_version",
+    "array",
+    "doc_hidden",
+    "case",
+    "prefix",
+    "result",
+    "returns_bool",
+    "checked",
+    "report",
+];
+
+impl Parse for Destructor {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // The types, until the first option
+        let mut types = vec![input.parse()?];
+        while input.peek(Token![,]) && !is_destructor_option(&input.fork()) {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            types.push(input.parse()?);
+        }
+        let mut name = None;
+        let mut abi = None;
+        let mut cfg = None;
+        let mut doc_hidden = false;
+        let mut case = Some(Case::Snake);
+        let mut prefix = None;
+        let mut abi_version = None;
+        let mut bulk = false;
+        let mut array = None;
+        let mut report = None;
+        let mut result = false;
+        let mut returns_bool = false;
+        let mut checked = false;
+
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            let options = Punctuated::<syn::Meta, Token![,]>::parse_terminated(input)?;
+            for option in options {
+                match option {
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("name") => {
+                        name = Some(parse_lit_str(&value.value)?);
+                    }
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("abi") => {
+                        let value = parse_lit_str(&value.value)?;
+                        if !ABIS.contains(&value.value().as_str()) {
+                            let message = format!(
+                                "unsupported ABI `{}`, expected one of: {}",
+                                value.value(),
+                                ABIS.join(", ")
+                            );
+                            return Err(syn::Error::new_spanned(value, message));
+                        }
+                        abi = Some(value);
+                    }
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("cfg") => {
+                        cfg = Some(parse_lit_str(&value.value)?.parse()?);
+                    }
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("prefix") => {
+                        prefix = Some(parse_lit_str(&value.value)?);
+                    }
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("case") => {
+                        let value = parse_lit_str(&value.value)?;
+                        case = match value.value().as_str() {
+                            "snake" => Some(Case::Snake),
+                            "pascal" => Some(Case::Pascal),
+                            "preserve" => None,
+                            _ => {
+                                let message = "expected `snake`, `pascal` or `preserve`";
+                                return Err(syn::Error::new_spanned(value, message));
+                            }
+                        };
+                    }
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("abi_version") => {
+                        abi_version = Some(parse_lit_int(&value.value)?);
+                    }
+                    syn::Meta::Path(ref path) if path.is_ident("array") => {
+                        bulk = true;
+                    }
+                    syn::Meta::Path(ref path) if path.is_ident("doc_hidden") => {
+                        doc_hidden = true;
+                    }
+                    syn::Meta::Path(ref path) if path.is_ident("result") => {
+                        result = true;
+                    }
+                    syn::Meta::Path(ref path) if path.is_ident("returns_bool") => {
+                        returns_bool = true;
+                    }
+                    syn::Meta::Path(ref path) if path.is_ident("checked") => {
+                        checked = true;
+                    }
+                    syn::Meta::List(ref list) if list.path.is_ident("array") => {
+                        array = Some(parse_array_argument(list)?);
+                    }
+                    syn::Meta::List(ref list) if list.path.is_ident("report") => {
+                        let mut out = None;
+                        list.parse_nested_meta(|meta| {
+                            if meta.path.is_ident("out") {
+                                let value: syn::LitStr = meta.value()?.parse()?;
+                                out = Some(value.parse()?);
+                                Ok(())
+                            } else {
+                                Err(meta.error("unsupported report option"))
+                            }
+                        })?;
+                        report = Some(
+                            out.ok_or_else(|| syn::Error::new_spanned(list, "missing `out`"))?,
+                        );
+                    }
+                    _ => return Err(syn::Error::new_spanned(option, "unsupported option")),
+                }
+            }
+        }
+
+        if bulk && (array.is_some() || report.is_some()) {
+            return Err(input.error("`array` cannot be combined with `array(...)` or `report`"));
+        }
+        if result && abi_version.is_some() {
+            return Err(input.error("`result` cannot be combined with `abi_version`"));
+        }
+        if result && returns_bool {
+            return Err(input.error("`result` cannot be combined with `returns_bool`"));
+        }
+        if checked && (result || returns_bool) {
+            return Err(input.error("`checked` cannot be combined with `result` or `returns_bool`"));
+        }
+        if name.is_some() && types.len() > 1 {
+            return Err(input.error("`name` cannot be combined with multiple types"));
+        }
+
+        Ok(Destructor {
+            types,
+            name,
+            abi,
+            cfg,
+            abi_version,
+            doc_hidden,
+            case,
+            prefix,
+            bulk,
+            array,
+            report,
+            result,
+            returns_bool,
+            checked,
+        })
+    }
+}
+
+/// Check if the comma is followed by an option rather than another type.
+fn is_destructor_option(input: ParseStream) -> bool {
+    let _ = input.parse::<Token![,]>();
+    match input.parse::<Ident>() {
+        Ok(ident) => {
+            DESTRUCTOR_OPTIONS.contains(&ident.to_string().as_str())
+                && (input.is_empty()
+                    || input.peek(Token![,])
+                    || input.peek(Token![=])
+                    || input.peek(syn::token::Paren))
+        }
+        Err(_) => false,
+    }
+}
+
+/// Parse a string literal option value.
+fn parse_lit_str(value: &syn::Expr) -> syn::Result<syn::LitStr> {
+    match value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(value),
+            ..
+        }) => Ok(value.clone()),
+        _ => Err(syn::Error::new_spanned(value, "expected string literal")),
+    }
+}
+
+/// Parse an integer literal option value.
+fn parse_lit_int(value: &syn::Expr) -> syn::Result<syn::LitInt> {
+    match value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(value),
+            ..
+        }) => {
+            value.base10_parse::<u32>()?;
+            Ok(value.clone())
+        }
+        _ => Err(syn::Error::new_spanned(value, "expected integer literal")),
+    }
+}
+
+/// Parse the `array(field = "...", elem = "...", len_arg)` option.
+fn parse_array_argument(list: &syn::MetaList) -> syn::Result<ArrayArgument> {
+    let mut field = None;
+    let mut elem = None;
+    let mut len_arg = false;
+    list.parse_nested_meta(|meta| {
+        if meta.path.is_ident("field") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            field = Some(value.parse()?);
+        } else if meta.path.is_ident("elem") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            elem = Some(value.parse()?);
+        } else if meta.path.is_ident("len_arg") {
+            len_arg = true;
+        } else {
+            return Err(meta.error("unsupported array option"));
+        }
+        Ok(())
+    })?;
+
+    let field = field.ok_or_else(|| syn::Error::new_spanned(list, "missing `field`"))?;
+    let elem = elem.ok_or_else(|| syn::Error::new_spanned(list, "missing `elem`"))?;
+    if !len_arg {
+        return Err(syn::Error::new_spanned(list, "missing `len_arg`"));
+    }
+    Ok(ArrayArgument { field, elem })
+}
+
+pub fn impl_extern_c_destructor(input: &Destructor) -> TokenStream {
+    input
+        .types
+        .iter()
+        .map(|ty| extern_c_destructor_of(input, ty))
+        .collect()
+}
+
+/// Generate the destructor of one of the types.
+fn extern_c_destructor_of(input: &Destructor, ty: &syn::Type) -> TokenStream {
+    match *ty {
+        syn::Type::Path(ref v) => {
+            let fn_ident = match function_ident(
+                v,
+                input.name.as_ref(),
+                input.case,
+                &input
+                    .prefix
+                    .as_ref()
+                    .map_or_else(|| "destruct_".to_string(), syn::LitStr::value),
+                match (input.bulk, input.checked) {
+                    (true, true) => "_array_checked",
+                    (true, false) => "_array",
+                    (false, true) => "_checked",
+                    (false, false) => "",
+                },
+            ) {
+                Ok(ident) => ident,
+                Err(err) => return err.to_compile_error(),
+            };
+
+            // Extra parameters and statements before freeing the structure
+            let mut params = Vec::new();
+            let mut on_null = Vec::new();
+            let mut statements = Vec::new();
+
+            if let Some(ref out) = input.report {
+                params.push(quote! { #out: *mut usize });
+                on_null.push(quote! {
+                    if !#out.is_null() {
+                        *#out = 0;
+                    }
+                });
+                // The fields, the array if any, and the structure itself
+                let array = input.array.as_ref().map(|array| {
+                    let field = &array.field;
+                    quote! { + if (*ptr).#field.is_null() { 0 } else { len } }
+                });
+                statements.push(quote! {
+                    if !#out.is_null() {
+                        *#out = (*ptr).destruct_count() #array + 1;
+                    }
+                });
+            }
+
+            // Free the array with the length provided by the caller
+            if let Some(ArrayArgument {
+                ref field,
+                ref elem,
+            }) = input.array
+            {
+                params.insert(0, quote! { len: usize });
+                statements.push(quote! {
+                    let array = (*ptr).#field as *mut #elem;
+                    if !array.is_null() {
+                        let _ = ::std::vec::Vec::from_raw_parts(array, len, len);
+                    }
+                });
+            }
+
+            // Free the structures of the array, then the array itself
+            let free = if input.bulk {
+                params.insert(0, quote! { n: usize });
+                quote! {
+                    let _ = ::std::vec::Vec::from_raw_parts(ptr, n, n);
+                }
+            } else {
+                quote! {
+                    let _ = ::std::boxed::Box::from_raw(ptr);
+                }
+            };
+
+            // The outcome returned to the caller, if any
+            let status = if input.returns_bool {
+                Some(Status {
+                    ty: quote! { bool },
+                    freed: quote! { true },
+                    null: quote! { false },
+                    mismatch: quote! { false },
+                })
+            } else if input.checked {
+                Some(Status {
+                    ty: quote! { ::std::ffi::c_int },
+                    freed: quote! { 0 },
+                    null: quote! { -1 },
+                    mismatch: quote! { -2 },
+                })
+            } else {
+                None
+            };
+
+            // Refuse to free a layout the caller was not built against
+            let mut version_check = TokenStream::new();
+            if let Some(ref version) = input.abi_version {
+                params.insert(0, quote! { abi_version: u32 });
+                let message = format!(
+                    "{}: ABI version {{}} does not match {}, skip destructing",
+                    fn_ident,
+                    version.base10_digits()
+                );
+                let version = version.base10_parse::<u32>().unwrap();
+                let skipped = status.as_ref().map(|status| &status.mismatch);
+                version_check = quote! {
+                    if abi_version != #version {
+                        ::std::eprintln!(#message, abi_version);
+                        #(#on_null)*
+                        return #skipped;
+                    }
+                };
+            }
+
+            let abi = match input.abi {
+                Some(ref abi) => abi.clone(),
+                None => syn::LitStr::new("C", Span::call_site()),
+            };
+
+            let cfg = input.cfg.as_ref().map(|cfg| quote! { #[cfg(#cfg)] });
+            let doc_hidden = if input.doc_hidden {
+                quote! { #[doc(hidden)] }
+            } else {
+                TokenStream::new()
+            };
+
+            // The C prototype, with the structure pointer first
+            let ret = if input.result {
+                Some(quote! { DestructResult })
+            } else {
+                status.as_ref().map(|status| status.ty.clone())
+            };
+            let mut c_params = vec![quote! { ptr: *mut #ty }];
+            c_params.extend(params.iter().cloned());
+            let attrs = quote! { #cfg #doc_hidden };
+            let prototype = prototype(attrs, &fn_ident, &c_params, ret.as_ref());
+
+            // Report the status, with the panics of nested drops caught
+            if input.result {
+                return quote! {
+                    #prototype
+                    #cfg
+                    #doc_hidden
+                    #[no_mangle]
+                    pub unsafe extern #abi fn #fn_ident(
+                        ptr: *mut #ty #(, #params)*
+                    ) -> DestructResult {
+                        if ptr.is_null() {
+                            #(#on_null)*
+                            return DestructResult::NullPtr;
+                        }
+                        let result = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| {
+                            #(#statements)*
+                            #free
+                        }));
+                        match result {
+                            ::std::result::Result::Ok(()) => DestructResult::Ok,
+                            ::std::result::Result::Err(_) => DestructResult::PanicCaught,
+                        }
+                    }
+                };
+            }
+
+            // Report whether the structure was freed, e.g. to detect double frees of a nulled pointer
+            if let Some(Status {
+                ty: ref ret,
+                ref freed,
+                ref null,
+                ..
+            }) = status
+            {
+                return quote! {
+                    #prototype
+                    #cfg
+                    #doc_hidden
+                    #[no_mangle]
+                    pub unsafe extern #abi fn #fn_ident(ptr: *mut #ty #(, #params)*) -> #ret {
+                        #version_check
+                        if ptr.is_null() {
+                            #(#on_null)*
+                            return #null;
+                        }
+                        #(#statements)*
+                        #free
+                        #freed
+                    }
+                };
+            }
+
+            quote! {
+                #prototype
+                #cfg
+                #doc_hidden
+                #[no_mangle]
+                pub unsafe extern #abi fn #fn_ident(ptr: *mut #ty #(, #params)*) {
+                    #version_check
+                    if ptr.is_null() {
+                        #(#on_null)*
+                        return;
+                    }
+                    #(#statements)*
+                    #free
+                }
+            }
+        }
+        ref ty => syn::Error::new_spanned(ty, "Not supported type").to_compile_error(),
+    }
+}
+
+/// The name of the generated function: `name` verbatim, or the name of the type in `case`, if any,
+/// between `prefix` and `suffix`. Generic types need `name`, symbols cannot embed the arguments.
+fn function_ident(
+    ty: &syn::TypePath,
+    name: Option<&syn::LitStr>,
+    case: Option<Case>,
+    prefix: &str,
+    suffix: &str,
+) -> syn::Result<Ident> {
+    if let Some(name) = name {
+        return name.parse::<Ident>().map_err(|err| {
+            syn::Error::new_spanned(name, format!("Invalid function name: {}", err))
+        });
+    }
+    let segment = match (&ty.qself, ty.path.segments.last()) {
+        (None, Some(segment)) => segment,
+        _ => return Err(syn::Error::new_spanned(ty, "Not supported type")),
+    };
+    if !segment.arguments.is_none() {
+        let message = "Generic types require an explicit `name = \"...\"`";
+        return Err(syn::Error::new_spanned(ty, message));
+    }
+    let name = segment.ident.to_string();
+    let name = match case {
+        Some(case) => name.to_case(case),
+        None => name,
+    };
+    let name = format!("{}{}{}", prefix, name, suffix);
+    let mut ident = syn::parse_str::<Ident>(&name).map_err(|_| {
+        syn::Error::new_spanned(ty, format!("`{}` is not a valid function name", name))
+    })?;
+    ident.set_span(segment.ident.span());
+    Ok(ident)
+}
+
+/// Generate `const <FUNCTION>_PROTOTYPE: &str`, the C prototype of the function, with the `header`
+/// feature. `params` are the Rust parameters, e.g. `ptr: *mut Structure`, and `attrs` the
+/// attributes of the function shared by the constant, e.g. its `cfg`.
+fn prototype(
+    attrs: TokenStream,
+    fn_ident: &Ident,
+    params: &[TokenStream],
+    ret: Option<&TokenStream>,
+) -> TokenStream {
+    if !cfg!(feature = "header") {
+        return TokenStream::new();
+    }
+
+    let param = |input: ParseStream| {
+        let name: Ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty: syn::Type = input.parse()?;
+        Ok(c_declaration(&c_type(&ty), &name.to_string()))
+    };
+    let params = params
+        .iter()
+        .filter_map(|tokens| param.parse2(tokens.clone()).ok())
+        .collect::<Vec<_>>();
+    let params = if params.is_empty() {
+        "void".to_string()
+    } else {
+        params.join(", ")
+    };
+    let ret = ret
+        .and_then(|ret| syn::parse2::<syn::Type>(ret.clone()).ok())
+        .map_or_else(|| "void".to_string(), |ret| c_type(&ret));
+    let function = c_declaration(&ret, &fn_ident.to_string());
+    let value = format!("{}({});", function, params);
+
+    let name = Ident::new(
+        &format!("{}_PROTOTYPE", fn_ident.to_string().to_uppercase()),
+        fn_ident.span(),
+    );
+    let doc = format!("The C prototype of `{}`.", fn_ident);
+    quote! {
+        #attrs
+        #[doc = #doc]
+        pub const #name: &str = #value;
+    }
+}
+
+/// The C spelling of a parameter or return type, the structures being opaque types of their name.
+fn c_type(ty: &syn::Type) -> String {
+    match *ty {
+        syn::Type::Ptr(ref ptr) => {
+            let elem = c_type(&ptr.elem);
+            match (elem.ends_with('*'), ptr.const_token.is_some()) {
+                (true, _) => format!("{}*", elem),
+                (false, true) => format!("const {} *", elem),
+                (false, false) => format!("{} *", elem),
+            }
+        }
+        syn::Type::Path(ref path) => {
+            let Some(segment) = path.path.segments.last() else {
+                return quote! { #path }.to_string();
+            };
+            let name = segment.ident.to_string();
+            let c_name = match name.as_str() {
+                "usize" => "size_t",
+                "isize" => "ptrdiff_t",
+                "u8" => "uint8_t",
+                "u16" => "uint16_t",
+                "u32" => "uint32_t",
+                "u64" => "uint64_t",
+                "i8" => "int8_t",
+                "i16" => "int16_t",
+                "i32" => "int32_t",
+                "i64" => "int64_t",
+                "f32" => "float",
+                "f64" => "double",
+                "c_char" => "char",
+                "c_int" => "int",
+                "c_uint" => "unsigned int",
+                "c_long" => "long",
+                "c_ulong" => "unsigned long",
+                "c_void" => "void",
+                _ => return name,
+            };
+            c_name.to_string()
+        }
+        syn::Type::Tuple(ref tuple) if tuple.elems.is_empty() => "void".to_string(),
+        ref ty => quote! { #ty }.to_string(),
+    }
+}
+
+/// Declare `name` of the C type, without a space after a pointer, e.g. `Structure *ptr`.
+fn c_declaration(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{}{}", ty, name)
+    } else {
+        format!("{} {}", ty, name)
+    }
+}
+
+/// Generate the `DestructResult` returned by the destructors with the `result` option.
+pub fn impl_destruct_result() -> TokenStream {
+    quote! {
+        /// Status of an extern destructor, with a stable `#[repr(C)]` ABI.
+        #[repr(C)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum DestructResult {
+            /// The structure was freed.
+            Ok = 0,
+            /// The pointer was null, nothing was freed.
+            NullPtr = 1,
+            /// A drop panicked, the panic was caught at the boundary.
+            PanicCaught = 2,
+        }
+    }
+}
+
+/// Arguments of the [`extern_c_clone!`](crate::extern_c_clone) macro.
+pub struct Cloner {
+    ty: syn::Type,
+    /// `name = "..."`: The function name, used verbatim.
+    name: Option<syn::LitStr>,
+    /// `cfg = "..."`: The configuration predicate the function is generated under.
+    cfg: Option<TokenStream>,
+}
+
+impl Parse for Cloner {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        let mut name = None;
+        let mut cfg = None;
+
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            let options = Punctuated::<syn::Meta, Token![,]>::parse_terminated(input)?;
+            for option in options {
+                match option {
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("name") => {
+                        name = Some(parse_lit_str(&value.value)?);
+                    }
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("cfg") => {
+                        cfg = Some(parse_lit_str(&value.value)?.parse()?);
+                    }
+                    _ => return Err(syn::Error::new_spanned(option, "unsupported option")),
+                }
+            }
+        }
+
+        Ok(Cloner { ty, name, cfg })
+    }
+}
+
+pub fn impl_extern_c_clone(input: &Cloner) -> TokenStream {
+    match input.ty {
+        syn::Type::Path(ref v) => {
+            let fn_ident =
+                match function_ident(v, input.name.as_ref(), Some(Case::Snake), "clone_", "") {
+                    Ok(ident) => ident,
+                    Err(err) => return err.to_compile_error(),
+                };
+            let ty = &input.ty;
+            let cfg = input.cfg.as_ref().map(|cfg| quote! { #[cfg(#cfg)] });
+            let prototype = prototype(
+                quote! { #cfg },
+                &fn_ident,
+                &[quote! { ptr: *const #ty }],
+                Some(&quote! { *mut #ty }),
+            );
+
+            quote! {
+                #prototype
+                #cfg
+                #[no_mangle]
+                pub unsafe extern "C" fn #fn_ident(ptr: *const #ty) -> *mut #ty {
+                    if ptr.is_null() {
+                        return ::std::ptr::null_mut();
+                    }
+                    let clone = <#ty as ::std::clone::Clone>::clone(&*ptr);
+                    ::std::boxed::Box::into_raw(::std::boxed::Box::new(clone))
+                }
+            }
+        }
+        ref ty => syn::Error::new_spanned(ty, "Not supported type").to_compile_error(),
+    }
+}
+
+/// Arguments of the [`extern_c_constructor!`](crate::extern_c_constructor) macro.
+pub struct Constructor {
+    ty: syn::Type,
+    /// `name = "..."`: The function name, used verbatim.
+    name: Option<syn::LitStr>,
+    /// `cfg = "..."`: The configuration predicate the function is generated under.
+    cfg: Option<TokenStream>,
+    /// `zeroed`: Allocate a zeroed structure instead of `Default::default()`.
+    zeroed: bool,
+}
+
+impl Parse for Constructor {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        let mut name = None;
+        let mut cfg = None;
+        let mut default = false;
+        let mut zeroed = false;
+
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            let options = Punctuated::<syn::Meta, Token![,]>::parse_terminated(input)?;
+            for option in options {
+                match option {
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("name") => {
+                        name = Some(parse_lit_str(&value.value)?);
+                    }
+                    syn::Meta::NameValue(ref value) if value.path.is_ident("cfg") => {
+                        cfg = Some(parse_lit_str(&value.value)?.parse()?);
+                    }
+                    syn::Meta::Path(ref path) if path.is_ident("default") => {
+                        default = true;
+                    }
+                    syn::Meta::Path(ref path) if path.is_ident("zeroed") => {
+                        zeroed = true;
+                    }
+                    _ => return Err(syn::Error::new_spanned(option, "unsupported option")),
+                }
+            }
+        }
+
+        if default && zeroed {
+            return Err(input.error("`default` cannot be combined with `zeroed`"));
+        }
+
+        Ok(Constructor {
+            ty,
+            name,
+            cfg,
+            zeroed,
+        })
+    }
+}
+
+pub fn impl_extern_c_constructor(input: &Constructor) -> TokenStream {
+    match input.ty {
+        syn::Type::Path(ref v) => {
+            let fn_ident =
+                match function_ident(v, input.name.as_ref(), Some(Case::Snake), "new_", "") {
+                    Ok(ident) => ident,
+                    Err(err) => return err.to_compile_error(),
+                };
+            let ty = &input.ty;
+
+            let value = if input.zeroed {
+                quote! { ::std::mem::zeroed::<#ty>() }
+            } else {
+                quote! { <#ty as ::std::default::Default>::default() }
+            };
+
+            // Balanced by the decrement in `drop`
+            let gauge = if cfg!(feature = "gauge") {
+                quote! {
+                    <#ty>::live_gauge().fetch_add(1, ::std::sync::atomic::Ordering::Relaxed);
+                }
+            } else {
+                TokenStream::new()
+            };
+
+            let cfg = input.cfg.as_ref().map(|cfg| quote! { #[cfg(#cfg)] });
+            let prototype = prototype(quote! { #cfg }, &fn_ident, &[], Some(&quote! { *mut #ty }));
+
+            quote! {
+                #prototype
+                #cfg
+                #[no_mangle]
+                pub unsafe extern "C" fn #fn_ident() -> *mut #ty {
+                    #gauge
+                    ::std::boxed::Box::into_raw(::std::boxed::Box::new(#value))
+                }
+            }
+        }
+        ref ty => syn::Error::new_spanned(ty, "Not supported type").to_compile_error(),
+    }
+}
+
+/// Generate the `atexit`-compatible destructor of a global `AtomicPtr<T>`.
+pub fn impl_atexit_destructor(global: &syn::Path) -> TokenStream {
+    let Some(segment) = global.segments.last() else {
+        return syn::Error::new_spanned(global, "Expected the path of a static").to_compile_error();
+    };
+    let ident = &segment.ident;
+    let name = ident.to_string().to_case(Case::Snake);
+    let fn_ident = Ident::new(&format!("atexit_{}", name), ident.span());
+
+    // Register the function at the first call only, e.g. while initializing the global
+    let register = if cfg!(feature = "libc") {
+        let register_ident = Ident::new(&format!("register_atexit_{}", name), ident.span());
+        quote! {
+            /// Register the destructor of the global with `atexit` once,
+            /// returning whether it succeeded.
+            pub fn #register_ident() -> bool {
+                // Declared here, the C library is linked by `std` anyway
+                extern "C" {
+                    fn atexit(callback: extern "C" fn()) -> ::std::ffi::c_int;
+                }
+                static REGISTERED: ::std::sync::OnceLock<bool> = ::std::sync::OnceLock::new();
+                *REGISTERED.get_or_init(|| unsafe { atexit(#fn_ident) } == 0)
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let prototype = prototype(TokenStream::new(), &fn_ident, &[], None);
+
+    quote! {
+        #prototype
+
+        /// Free the object of the global, leaving it null.
+        ///
+        /// The global must be null or hold a pointer from `Box::into_raw`.
+        pub extern "C" fn #fn_ident() {
+            let ptr = #global.swap(
+                ::std::ptr::null_mut(),
+                ::std::sync::atomic::Ordering::AcqRel,
+            );
+            if !ptr.is_null() {
+                let _ = unsafe { ::std::boxed::Box::from_raw(ptr) };
+            }
+        }
+
+        #register
+    }
+}
@@ -86,10 +86,11 @@
 //! pub struct MyStruct {
 //!     field: *mut std::ffi::c_char,
 //! }
+//! #[allow(unused_unsafe)]
 //! impl ::std::ops::Drop for MyStruct {
 //!     fn drop(&mut self) {
 //!         unsafe {
-//!             let _ = ::std::ffi::CString::from_raw(self.field as *mut ::std::ffi::c_char);
+//!             let _ = ::std::ffi::CString::from_raw(self.field);
 //!         }
 //!     }
 //! }
@@ -107,6 +108,7 @@
 //!     pub normal_int: u32,
 //!     pub normal_string: String,
 //! }
+//! #[allow(unused_unsafe)]
 //! impl ::std::ops::Drop for Structure {
 //!     fn drop(&mut self) {
 //!         unsafe {
@@ -114,17 +116,13 @@
 //!                 self.c_string as *mut ::std::ffi::c_char,
 //!             );
 //!             if !self.c_string_nullable.is_null() {
-//!                 let _ = ::std::ffi::CString::from_raw(
-//!                     self.c_string_nullable as *mut ::std::ffi::c_char,
-//!                 );
+//!                 let _ = ::std::ffi::CString::from_raw(self.c_string_nullable);
 //!             }
-//!             let _ = ::std::boxed::Box::from_raw(self.other as *mut MyStruct);
+//!             let _ = ::std::boxed::Box::from_raw(self.other);
 //!             if !self.other_nullable.is_null() {
-//!                 let _ = ::std::boxed::Box::from_raw(
-//!                     self.other_nullable as *mut MyStruct,
-//!                 );
+//!                 let _ = ::std::boxed::Box::from_raw(self.other_nullable);
 //!             }
-//!             let _ = ::std::boxed::Box::from_raw(self.any as *mut AnyOther);
+//!             let _ = ::std::boxed::Box::from_raw(self.any);
 //!         }
 //!     }
 //! }
@@ -163,32 +161,451 @@
 //! ```
 
 mod destruct;
+mod extern_c;
 mod utils;
 
 use convert_case::{Case, Casing};
 use proc_macro2::{Ident, TokenStream};
-use quote::{quote, quote_spanned, ToTokens};
+use quote::{quote, quote_spanned};
 use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput};
 
 /// The [`Destruct`] derive macro.
 ///
 /// Generate a destructor for the structure.
+/// The [`ffi_destruct`](macro@ffi_destruct) attribute macro is an alternative entry point.
+///
+/// Pointer fields behind a lock, i.e. `Mutex<*mut T>` or `RwLock<*mut T>`, are freed as the inner
+/// pointer. It is read with `get_mut()` in `drop`, so no locking is needed, and poisoned locks
+/// are freed as well. The field attributes apply to the inner pointer.
+///
+/// Unions are supported with exactly one field marked `#[union_active]`, the live pointer freed
+/// like a field of a structure, e.g. for a C tagged union whose tag is fixed for the type.
+/// It cannot be combined with `builder` or `defer`.
+///
+/// Inline arrays of raw pointers, e.g. `slots: [*mut Node; 16]`, free each slot like a field.
+/// The slots are always null-checked, since partially filled arrays are common.
+///
+/// `NonNull<T>` fields are freed as a `*mut T`, without a null check. `Option<NonNull<T>>` fields
+/// are nullable, `None` is skipped, e.g. `Option<NonNull<c_char>>` for an optional C string.
+///
+/// Generic structures keep their lifetime, type and const parameters and bounds, e.g.
+/// `struct Wrapper<'a, T> { ptr: *mut T, _m: PhantomData<&'a T> }`.
+///
+/// Pointers to slices and trait objects, e.g. `*mut [u8]` or `*mut dyn Trait`, are freed through
+/// the fat pointer, which carries the length or the vtable. So are generic `?Sized` pointees,
+/// e.g. `struct Erased<T: ?Sized> { ptr: *mut T }` for `Erased<dyn Trait>` or `Erased<[u8]>`.
+///
+/// Double pointers, e.g. `*mut *mut T`, free each non-null inner pointer like a field, then the
+/// outer allocation: a `Box<*mut T>`, or a `Vec<*mut T>` with `#[array(len = "...")]`, e.g. for a
+/// `*mut *mut c_char` array of C strings. The inner pointers accept no attributes of their own.
+///
+/// Tuple structures are supported as well, e.g. `struct Handle(*mut c_char, #[nullable] *mut T)`,
+/// their fields are freed as `self.0`, `self.1`, and so on. Unit structures get an empty `Drop`,
+/// without the warning for structures without pointer fields, so code generators can derive
+/// [`Destruct`] on every structure they emit.
 ///
 /// ## Field Attributes
 /// - `#[nullable]` - The field is nullable, the destructor will check if the pointer is null before
-/// - `#[no_drop]` - The field will not be added to the destructor
-#[proc_macro_derive(Destruct, attributes(nullable, no_drop))]
+/// - `#[nullable(sentinel = "...")]` - The field is nullable, and the pointer equal to the
+///   sentinel expression is skipped as well, e.g. `#[nullable(sentinel = "usize::MAX as *mut _")]`
+///   for an invalid handle value of a C library.
+/// - `#[non_null]` - The field is not null-checked, overriding `#[destruct(nullable)]`
+/// - `#[no_drop]` - The field will not be added to the destructor. Accepted on any field, so it
+///   can be applied uniformly, e.g. by code generators; other fields are never freed anyway.
+/// - `#[owned]` - Generate `unsafe fn destruct_partial(ptr: *mut Self)`, which frees only the
+///   fields marked `#[owned]` and then the structure without dropping it. Useful for
+///   incrementally-constructed structures, where only those fields are initialized.
+///   It is `#[track_caller]`, so the panics of the generated checks point to the call.
+/// - `#[interned]` - The field points to an interned string owned by an intern pool, and is never
+///   freed, like `#[no_drop]`. With `#[interned = "..."]`, debug builds also assert the pointer is
+///   in the pool, e.g. `#[interned = "crate::pool::contains"]` with
+///   `fn contains(ptr: *const c_char) -> bool`, catching non-interned strings marked by mistake.
+/// - `#[alias_of = "..."]` - The field points to the same allocation as the named field, and is
+///   never freed on its own, like `#[no_drop]`. Debug builds assert both pointers are equal.
+/// - `#[drop_if = "..."]` - The field is only freed if the expression evaluates to `true`,
+///   e.g. `#[drop_if = "self.owned"]` or `#[drop_if = "crate::OWNS.load(Ordering::Relaxed)"]`.
+///   Any expression is accepted, and it is evaluated inside the destructor.
+/// - `#[free_if_callback = "..."]` - The field is only freed if the named callback field,
+///   an `Option<extern "C" fn(*mut c_void) -> bool>`, returns `true` for the pointer,
+///   e.g. `#[free_if_callback = "should_free"]` lets the host veto individual frees.
+///   Without a callback, the field is freed. A null `#[nullable]` field is never passed to it.
+/// - `#[owned_if = "..."]` - The field is only freed if the named `bool` field is `true`,
+///   i.e. the structure owns the pointer rather than borrowing it. In debug builds, the pointer
+///   is asserted to be non-null when owned, unless the field is also `#[nullable]`.
+/// - `#[init_guard = "..."]` - The field is only freed if the named `std::sync::Once` field has
+///   completed, for pointers initialized lazily with `call_once`. Until then, the field may hold
+///   anything and is never read.
+/// - `#[cfg_not_miri_skip]` - Skip freeing the field under `cfg(miri)`, deliberately leaking it,
+///   e.g. for allocations from C that Miri cannot model. The rest of the code can then be tested
+///   with Miri, which reports the leak unless run with `-Zmiri-ignore-leaks`.
+/// - `#[reclaim = "..."]` - Reclaim the pointer with the `from_raw` of the given container instead,
+///   e.g. `#[reclaim = "Rc<RefCell<Inner>>"]` for a pointer from `Rc::into_raw`.
+///   Any container with a `from_raw` taking the pointer is accepted, such as `Box`, `Rc`, `Arc`,
+///   their `Weak`, or the pointer wrappers of ABI-stability crates like `abi_stable`'s `RBox`.
+/// - `#[ptr(box = "...")]` - Reclaim the pointer with the `from_raw` of the given box type of the
+///   pointee, e.g. `#[ptr(box = "abi_stable::std_types::RBox")]` on a `*mut T` reclaims it as an
+///   `RBox<T>`, like `#[reclaim]` without spelling out the pointee.
+/// - `#[drop_with = "..."]` - Free the pointer by calling the given function instead, e.g.
+///   `#[drop_with = "libc::free"]` for memory from the C allocator, or `sqlite3_free`.
+///   The pointer is cast to the parameter type, e.g. the `*mut c_void` of `libc::free`.
+/// - `#[erased(drop_fn = "...")]` - The field is a type-erased `*mut ()`, freed by calling the
+///   drop function stored in the named field, i.e. `(self.drop_fn)(self.field)`, such as an
+///   `unsafe fn(*mut ())` or `unsafe extern "C" fn(*mut ())` paired with the payload.
+/// - `#[array(len = "...")]` - The field points to an array of `len` elements, reclaimed as a
+///   `Vec` with equal length and capacity. `len` is either a field of the structure,
+///   e.g. `#[array(len = "count")]`, any expression, e.g. `#[array(len = "self.size.count()")]`,
+///   or an integer literal for buffers of a constant length, e.g. `#[array(len = 256)]`.
+///   Every element is dropped, and a `*mut c_char` is reclaimed as a `Vec<c_char>` rather than a
+///   `CString`. A null pointer is skipped, as returned by C APIs for empty arrays, and debug builds
+///   assert its length is zero. With `require_drop`, e.g. `#[array(len = "count", require_drop)]`,
+///   the element type is asserted at compile time to need dropping, e.g. when the elements are
+///   structures deriving `Destruct` themselves.
+/// - `#[vec(len = "...", cap = "...")]` - The field points to the buffer of a `Vec` leaked with
+///   a capacity different from its length, e.g. by `Vec::into_raw_parts`, reclaimed with both.
+///   `len` and `cap` take the same forms as the `len` of `#[array(...)]`, e.g.
+///   `#[vec(len = "len", cap = "cap")]`, and other attributes treat it as an array of `len`
+///   elements. Reclaiming with the wrong capacity is undefined behavior, debug builds assert the
+///   length does not exceed it.
+/// - `#[byte_string(len = "...")]` - The field points to a byte string of `len` bytes which is
+///   not NUL-terminated, e.g. a `*mut c_char` or `*mut u8` handed back by a C API with its length.
+///   It is reclaimed as a `Vec<u8>` with equal length and capacity, instead of scanning for a NUL
+///   like `CString`. `len` is either a field of the structure or any expression, as with `array`.
+/// - `#[c_string]` - Reclaim the pointer with `CString::from_raw`, whatever its element type,
+///   e.g. a `*mut i8` or `*const u8` C string of hand-written bindings. Pointers to a type path
+///   ending in `c_char` are C strings without it. The element type must be byte-sized.
+/// - `#[wide]` - The field points to a NUL-terminated wide string of 16-bit units, e.g. a
+///   `*mut u16` UTF-16 string for Windows APIs. It is reclaimed as a `Vec<u16>` up to and
+///   including the NUL, so it must be allocated with equal length and capacity, e.g. from
+///   `Box<[u16]>`. With `#[array(len = "...")]`, the array length is used instead of scanning,
+///   and counts the units including the NUL, e.g. 6 for `"sized"`.
+/// - `#[flush_before_drop = "..."]` - Call the method on the pointee before freeing it,
+///   e.g. `#[flush_before_drop = "flush"]` for a `*mut BufWriter<W>`. The method takes `&mut self`
+///   and its result is ignored. It must be in scope, e.g. `use std::io::Write;`.
+/// - `#[union_active]` - The live field of a union, the only one freed. Exactly one field of a
+///   union must be marked.
+/// - `#[pre_drop = "..."]` - Call the method on the pointee before freeing it, inside the same
+///   null check, e.g. `#[pre_drop = "close"]` to release a C handle held by the pointee. Unlike
+///   `flush_before_drop`, the result is not ignored, so the method returns `()`. It runs after
+///   `flush_before_drop`.
+/// - `#[pre_free]` - Call `pre_free()` on the pointee before freeing it, after any
+///   `flush_before_drop` and `pre_drop`. This is the hook of a `PreFree` trait, which the crate
+///   defining the pointee declares and keeps in scope, since a derive crate cannot export it:
+///   ```ignore
+///   trait PreFree {
+///       unsafe fn pre_free(&mut self);
+///   }
+///   ```
+///   Unlike the pointee's own `Drop`, the hook only runs when the pointee is freed as the field of
+///   a parent structure, e.g. to detach it from the parent before teardown.
+/// - `#[drop_order(N)]` - The priority of the field, lower first, `0` by default, e.g.
+///   `#[drop_order(-1)]`. Fields of equal priority are freed in declaration order.
+///   See [Ordering](#ordering).
+///
+/// ## Ordering
+/// The fields are freed in declaration order, unless reordered with `#[drop_order(N)]`.
+/// This is the canonical solution for children holding back-pointers into their parent:
+/// a child whose `Drop` reads a buffer owned by the parent must be freed before the buffer,
+/// otherwise it reads freed memory. So the children are declared first, or the buffer is given a
+/// higher `#[drop_order(N)]`.
+///
+/// ```
+/// # use ffi_destruct::Destruct;
+/// pub struct Registry(Vec<u32>);
+///
+/// pub struct Child {
+///     id: u32,
+///     // Back-pointer into the parent
+///     registry: *mut Registry,
+/// }
+///
+/// impl Drop for Child {
+///     fn drop(&mut self) {
+///         let id = self.id;
+///         unsafe { (*self.registry).0.retain(|&other| other != id) };
+///     }
+/// }
+///
+/// #[derive(Destruct)]
+/// pub struct Parent {
+///     child: *mut Child,
+///     registry: *mut Registry,
+/// }
+/// ```
+///
+/// The pointers are freed by the derived `Drop::drop`, which runs before the compiler drops the
+/// fields themselves, in declaration order. So every pointer is freed before any other field
+/// with a `Drop` of its own, e.g. a `File` or a `Vec`, wherever it is declared.
+/// `#[drop_order(N)]` only reorders the pointers among themselves.
+///
+/// A derive cannot postpone freeing until after the other fields are dropped, since they are
+/// dropped after the generated code returns. To free the pointers afterwards, move them into
+/// a nested structure deriving [`Destruct`], declared after the fields to drop first:
+///
+/// ```
+/// # use ffi_destruct::Destruct;
+/// # use std::ffi::c_char;
+/// #[derive(Destruct)]
+/// pub struct Buffers {
+///     name: *mut c_char,
+/// }
+///
+/// // No `Drop`: `file` is dropped first, then `buffers` frees its pointers
+/// pub struct Session {
+///     file: std::fs::File,
+///     buffers: Buffers,
+/// }
+/// ```
+///
+/// ## Container Attributes
+/// - `#[destruct(cfg = "...")]` - Only implement `Drop` under the configuration predicate,
+///   e.g. `#[destruct(cfg = "unix")]`. On other configurations the structure has no `Drop`.
+/// - `#[destruct(iterative, child = "...", len = "...")]` - Free a tree of nodes without
+///   recursion. `child` is a `*mut *mut Self` field pointing to an array of `len` owned children,
+///   allocated as a `Vec` (or boxed slice) with equal length and capacity, each child allocated
+///   with `Box`. The descendants are freed with a work-stack, so deep trees cannot overflow the
+///   stack in `drop`.
+/// - `#[destruct(nullable)]` - Null-check every pointer field as if marked `#[nullable]`,
+///   except the fields marked `#[non_null]`. `#[no_drop]` fields are still skipped.
+/// - `#[destruct(zero_on_drop)]` - Set each pointer field to null right after freeing it, so
+///   running the destructor again, e.g. after freeing the structure by hand, finds nothing to
+///   free. Only fields null-checked with `nullable` skip the null pointers safely.
+///   Cannot be combined with `defer`, which frees the pointers after the structure is gone.
+/// - `#[destruct(require_repr_c)]` - Reject the structure unless it is `#[repr(C)]` or
+///   `#[repr(transparent)]`, since the layout of other structures is unreliable across the FFI
+///   boundary. Other representation hints can be combined, e.g. `#[repr(C, packed)]`.
+/// - `#[destruct(strict)]` - Reject the fields which are neither pointers nor of a type known to
+///   free itself, unless marked `#[no_drop]`, to catch pointers hidden in other types, e.g.
+///   `Option<*mut T>`, or a type alias of a pointer. The known types are the primitives, C types
+///   like `c_int`, references, function pointers, atomics, and the owned types of `std` like
+///   `String`, `CString`, `Box`, `Vec` and `Option`, as long as their type arguments are known
+///   as well. Generic parameters are unknown. `PhantomData` fields own nothing and are always
+///   accepted, e.g. `PhantomData<*const u8>` or `PhantomData<T>`.
+/// - `#[destruct(with = "...")]` - Free the whole structure with the named function instead of
+///   the fields, e.g. `#[destruct(with = "ffi::MyStruct_free")]` for a C cleanup function freeing
+///   the members. It is called as `MyStruct_free(self as *mut Self)` in `drop`, must not free the
+///   structure itself, and the field attributes are ignored. Cannot be combined with `defer`.
+/// - `#[destruct(log = "...")]` - Log `dropping Structure` at the start of `drop` with the `log`
+///   feature, at one of the `log` levels `error`, `warn`, `info`, `debug` or `trace`, e.g.
+///   `#[destruct(log = "debug")]`. Without the feature, the attribute is accepted and ignored.
+/// - `#[destruct(pod_ok)]` - Skip generating `Drop` if no field needs to be freed, so the structure
+///   can stay `Copy`. Without it, deriving on such a structure emits a warning.
+/// - `#[destruct(gauge)]` - Register the `live_gauge()` of the type in the `gauge` module of
+///   [`gauge_registry!`] with the `gauge` feature, which must then be invoked at the crate root.
+///   Without the feature, the attribute is accepted and ignored.
+/// - `#[destruct(fuzz)]` - Record each address freed by the destructor in the `fuzz` module of
+///   [`fuzz_log!`] with the `fuzz` feature, which must then be invoked at the crate root.
+///   Without the feature, the attribute is accepted and ignored.
+/// - `#[destruct(report)]` - Generate `fn destruct_count(&self) -> usize`, the number of objects
+///   the destructor frees for the fields: one for each pointer, and the number of elements for
+///   arrays. Used by the `report` option of [`extern_c_destructor!`].
+/// - `#[destruct(refcount = "...")]` - Intrusive reference counting with the named `AtomicUsize`
+///   field. Every `drop` releases one reference, and the fields are only freed by the last one.
+///   Each reference is released by running `drop` in place on the shared structure, e.g. with
+///   [`std::ptr::drop_in_place`], so the fields other than pointers must not have drop glue of
+///   their own, which is asserted at compile time.
+///   The decrement uses `Ordering::AcqRel`: the release makes each holder's prior writes visible,
+///   and the last holder acquires them all before freeing.
+/// - `#[destruct(canary = "...", value = "...")]` - Check the named field against the value
+///   before freeing anything, e.g. `#[destruct(canary = "magic", value = "0xDEADBEEF")]`.
+///   On mismatch, e.g. a wrong pointer passed from C or a use-after-free, nothing is freed to
+///   avoid corrupting memory the structure does not own. Debug builds panic instead.
+/// - `#[destruct(skip_if_poisoned = "...")]` - Leak everything if the named `bool` field is set,
+///   e.g. after a panic left the structure in an inconsistent state. Freeing with broken
+///   invariants may be unsound, so the memory is deliberately leaked and a warning is printed to
+///   stderr instead.
+/// - `#[destruct(notify_sender = "...")]` - Send `()` on the named sender field after everything
+///   is freed, e.g. a `std::sync::mpsc::Sender<()>` signalling the teardown to another thread.
+///   Send errors are ignored.
+/// - `#[destruct(consumed_flag = "...")]` - Skip freeing anything if the named `bool` field is
+///   set, e.g. after the ownership of the fields was handed to C. Generates
+///   `fn into_ffi(&mut self)` setting the flag, to call before passing the pointers on, so the
+///   Rust side cannot double-free them.
+/// - `#[destruct(destructible)]` - Implement the `Destructible` trait generated by
+///   [`destructible!`], whose `unsafe fn free_raw(ptr: *mut Self)` frees the structure like the
+///   function of [`extern_c_destructor!`], for generic code over the structures.
+/// - `#[destruct(manual)]` - Generate `fn destruct(self)`, freeing the structure explicitly as
+///   dropping it would. It is `#[track_caller]`, so the panics of the generated checks, e.g. of
+///   `canary` in debug builds, point to the call instead of the generated `drop`.
+/// - `#[destruct(builder)]` - Generate `StructureBuilder` (the name of the structure followed by
+///   `Builder`) for field-by-field construction. It has a setter `with_<field>` for each field,
+///   e.g. `with_name(value)`, and `build()` returning the structure once every field is set, or
+///   the builder otherwise. Dropping the builder, e.g. on early return after an allocation failed
+///   midway, frees and drops only the fields set so far. Expressions like `drop_if` must only read fields which are set.
+/// - `#[destruct(defer)]` - Free the fields on a background thread instead of the thread dropping
+///   the structure, e.g. for slow teardown like closing network handles. Each type has its own
+///   worker thread, spawned on first use. The pointees are asserted at compile time to be `Send`.
+///   The dropping thread reads the fields, e.g. the pointers behind locks, checks `drop_if` and
+///   the other conditions, and only sends the pointers, the lengths and the drop functions of
+///   `erased` to the worker, which calls the `flush_before_drop`, `pre_drop` and `pre_free`
+///   methods and frees the pointees. The other fields are dropped as usual on the dropping
+///   thread. `fn flush_deferred()` waits until the worker has freed every object of the type
+///   dropped so far, since the pending frees are lost when the process exits.
+///   Cannot be combined with `release_via` or `notify_sender`, which need the structure itself,
+///   or `iterative`. Generic structures are not supported, since the worker thread is shared by
+///   every instantiation.
+/// - `#[release_via = "..."]` - COM-style release through a vtable, e.g.
+///   `#[release_via = "self.vtable, release"]` calls `((*self.vtable).release)(self)` with the
+///   structure as a `*mut c_void`, before the fields are freed. The vtable pointer is skipped if
+///   null. Otherwise it must point to a valid vtable whose `release` field is an
+///   `unsafe extern "C" fn(*mut c_void)`, which must not free the structure itself.
+///   A vtable pointer field is not owned, so mark it `#[no_drop]`.
+///
+/// ## Errors
+/// Misused field attributes are reported as compile errors pointing at the field, e.g.
+/// `Nullable attribute is only supported for raw pointers`:
+/// ```compile_fail
+/// use ffi_destruct::Destruct;
+///
+/// #[derive(Destruct)]
+/// pub struct Structure {
+///     #[nullable]
+///     len: u32,
+/// }
+/// ```
+///
+/// Malformed attributes are reported at the attribute, e.g. an invalid `#[drop_if = "..."]`
+/// expression, `#[array(...)]` without `len` or an unsupported `#[destruct(...)]` option.
+/// The messages are checked by the programs of `tests/ui`.
+///
+/// Attributes which would be ignored in favour of another are rejected as well, e.g.
+/// `#[no_drop]` with any attribute freeing the field, or two ways of freeing it like
+/// `#[drop_with = "..."]` and `#[c_string]`, reported as
+/// ``"`drop_with` attribute cannot be combined with `c_string`"``.
+///
+/// A repeated attribute, e.g. `#[nullable]` twice, is a warning, since only the first one applies:
+/// ``"duplicate `#[nullable]` attribute, only the first one applies"``.
+///
+/// `*mut c_void` pointees are opaque, so the field needs `#[drop_with = "..."]` with the function
+/// of their allocator instead of `Box`, otherwise it is an error at the type:
+/// ``"`c_void` pointers cannot be freed with `Box`, free them with `#[drop_with = "..."]`"``.
+///
+/// With `#[destruct(require_repr_c)]`, structures without `#[repr(C)]` or `#[repr(transparent)]` are
+/// rejected: ``"Structure requires `#[repr(C)]` or `#[repr(transparent)]` for a stable layout"``.
+///
+/// Unions must mark exactly one field `#[union_active]`, otherwise the fields are rejected:
+/// ``"Exactly one union field must be marked `#[union_active]`"``.
+///
+/// In strict mode, fields of unknown types are errors unless marked `#[no_drop]`, e.g. a
+/// `handle: Handle` field of `type Handle = *mut u8`, which the derive cannot see through:
+/// ``"Field of unknown type in strict mode, mark it `#[no_drop]` if it needs no manual freeing"``.
+///
+/// ## Features
+/// - `std` (default) - Reference `std` in the generated code. Without it, the generated code
+///   references `core` where possible instead, e.g. `::core::ops::Drop`, and still compiles in
+///   crates using `std`.
+///   The options relying on `std` still reference it: lock pointers, `skip_if_poisoned`,
+///   `defer`, the `abi_version` and `result` options of [`extern_c_destructor!`],
+///   [`atexit_destructor!`], and the `gauge`, `fuzz`, `trace`, `size-log`, `panic-safe` and
+///   `libc` features.
+/// - `alloc` - Without `std`, reference `alloc` for the allocating items, e.g.
+///   `::alloc::boxed::Box` and `::alloc::ffi::CString`, for `no_std` crates with an allocator.
+///   Every crate using the macros must then declare `extern crate alloc;`.
+/// - `defmt` - Emit a `defmt::trace!` message before freeing each field, e.g.
+///   `destruct: Structure::field`. The crate using the derive must depend on `defmt`.
+/// - `gauge` - Generate `fn live_gauge() -> &'static AtomicUsize` counting the live objects of
+///   the type. Constructing an object must increment it, and the destructor decrements it, also
+///   when it returns early for `consumed_flag` or `skip_if_poisoned`. With `refcount`, every
+///   released reference decrements it, so it counts the references. `pod_ok` types without `Drop`
+///   still have the gauge, for [`extern_c_constructor!`], but nothing decrements it.
+///   `#[destruct(gauge)]` registers the gauge of the type in the registry of [`gauge_registry!`],
+///   for monitoring.
+/// - `fuzz` - Record the addresses freed by the destructors of the `#[destruct(fuzz)]` types in
+///   the crate-wide log of [`fuzz_log!`], in order. A fuzzing harness can compare the log with the
+///   addresses it allocated across a sequence of operations, e.g. to catch an address freed more
+///   often than it was allocated, and clear it between runs. With `cargo fuzz`, AddressSanitizer
+///   already aborts on the double free itself, and the log tells which field sequence led there.
+/// - `schema` - Generate `const DESTRUCT_SCHEMA: &str`, a JSON description of how each field is
+///   freed for tooling, e.g. to cross-check against C headers:
+///   `{"type":"Structure","fields":[{"name":"data","free":"array","nullable":true,"len":"self.len"}]}`.
+///   `free` is one of `box`, `c_string`, `byte_string`, `array`, `reclaim`, `drop_with`, `wide_string`,
+///   `erased`, `no_drop`, `alias`, `interned`, `children` for the children of `iterative`, or
+///   `none` for fields which are not pointers.
+/// - `metrics` - Increment the `ffi_destruct_freed` counter of the `metrics` facade for each
+///   freed field, labeled with the type of the structure: `"type" => "Structure"`.
+///   The crate using the derive must depend on `metrics`.
+/// - `log` - Log the drops of the structures marked `#[destruct(log = "...")]` with the `log`
+///   facade. The crate using the derive must depend on `log`.
+/// - `libc` - Generate the `atexit` registration of [`atexit_destructor!`], which declares the
+///   `atexit` of the C library itself. The crate using the macro needs no `libc` dependency.
+/// - `trace` - Print a message to stderr before freeing each field, in the order the fields are
+///   freed, e.g. `destruct: Structure::field`. Without it, the generated code is unchanged.
+/// - `panic-safe` - Free each field under `catch_unwind` in `drop`, so a panicking `Drop` of a
+///   pointee does not leak the fields after it. Once every field is processed, the first panic is
+///   resumed. `destruct_partial()` is unaffected, to keep reporting its caller on panics.
+///   With `panic = "abort"`, the first panic aborts the process as usual. Dropping the structure
+///   while already unwinding from another panic aborts as well if a field panics, like any
+///   panicking `Drop`.
+/// - `header` - Generate `const <FUNCTION>_PROTOTYPE: &str` next to each extern function of the
+///   macros, its C prototype with the structures as opaque types, e.g.
+///   `DESTRUCT_STRUCTURE_PROTOTYPE` is `void destruct_structure(Structure *ptr);`. A build step or
+///   test can write them to a header after `typedef struct Structure Structure;`, with
+///   `<stddef.h>` and `<stdint.h>` for the integer types. The prototypes assume the C ABI.
+/// - `size-log` - Print the total bytes freed for the fields to stderr on each drop, e.g.
+///   `destruct: Structure freed 24 bytes`: `size_of` the pointee for each pointer, `len` times it
+///   for arrays, `len` for byte strings, and the length with the nul terminator for C strings.
+///   Type-erased pointees have no known size and are not counted.
+#[proc_macro_derive(
+    Destruct,
+    // Keep in sync with `utils::HELPER_ATTRIBUTES`
+    attributes(
+        nullable, non_null, no_drop, owned, drop_if, owned_if, flush_before_drop, pre_drop,
+        pre_free, alias_of, init_guard, interned, free_if_callback, cfg_not_miri_skip, drop_order,
+        erased, reclaim, ptr, drop_with, array, vec, byte_string, wide, c_string, union_active,
+        release_via, destruct
+    )
+)]
 pub fn destruct_macro_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let expand = destruct::impl_destruct_macro(&input);
 
-    proc_macro::TokenStream::from(expand)
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
+}
+
+/// The [`ffi_destruct`](macro@ffi_destruct) attribute macro.
+///
+/// An alternative to `#[derive(Destruct)]`, sharing the same implementation and attributes.
+/// The structure is left intact apart from removing the helper attributes.
+///
+/// ## Options
+/// - `extern_c` - Also generate the destructor of [`extern_c_destructor!`].
+/// - `repr_c` - Add `#[repr(C)]` to the structure.
+///
+/// ## Usage
+/// ```
+/// use ffi_destruct::ffi_destruct;
+///
+/// #[ffi_destruct(extern_c, repr_c)]
+/// pub struct MyStruct {
+///     #[nullable]
+///     field: *mut std::ffi::c_char,
+/// }
+///
+/// // Generated: destruct_my_struct()
+/// unsafe { destruct_my_struct(std::ptr::null_mut()) };
+/// ```
+#[proc_macro_attribute]
+pub fn ffi_destruct(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let mut options = destruct::AttributeOptions::default();
+    let parser = syn::meta::parser(|meta| options.parse(meta));
+    parse_macro_input!(args with parser);
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let expand = destruct::impl_ffi_destruct_attribute(&options, &input);
+
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
 }
 
 /// Generate extern "C" destructor for provide type
 ///
-/// Provide the function name: "destruct_" + snake_case name of the type.
+/// Provide the function name: "destruct_" + snake_case name of the type, see [Prefix](#prefix).
+///
+/// The type can be any type freed by its own `Drop`, not only structures deriving [`Destruct`],
+/// including an alias of a generic instantiation: `extern_c_destructor!(StringList)` with
+/// `type StringList = List<CString>;` generates `destruct_string_list(ptr: *mut StringList)`.
 ///
 /// ## Usage
 ///
@@ -217,26 +634,463 @@ pub fn destruct_macro_derive(input: proc_macro::TokenStream) -> proc_macro::Toke
 ///     let _ = ::std::boxed::Box::from_raw(ptr);
 /// }
 /// ```
+///
+/// ## Custom name
+/// `name = "..."` sets the function name verbatim, instead of the default "destruct_" prefix.
+/// The macro can be invoked several times with different names for the same type,
+/// e.g. to export the destructor under the symbol conventions of different consumers.
+///
+/// ```
+/// # use ffi_destruct::{Destruct, extern_c_destructor};
+/// # #[derive(Destruct)]
+/// # pub struct MyStruct {
+/// #    field: *mut std::ffi::c_char,
+/// # }
+/// extern_c_destructor!(MyStruct, name = "mylib_my_struct_free");
+/// extern_c_destructor!(MyStruct, name = "MyStruct_destroy");
+/// ```
+///
+/// ## Case
+/// `case = "..."` sets how the type name is converted after the "destruct_" prefix: `"snake"` by
+/// default, `"pascal"`, or `"preserve"` to keep it as is, e.g.
+/// `extern_c_destructor!(MyStruct, case = "preserve")` generates `destruct_MyStruct()`.
+///
+/// ## Prefix
+/// `prefix = "..."` replaces the "destruct_" prefix, e.g.
+/// `extern_c_destructor!(MyStruct, prefix = "free_")` generates `free_my_struct()`. The prefix can
+/// be empty, as long as the resulting name is a valid identifier:
+///
+/// ```compile_fail
+/// # use ffi_destruct::{Destruct, extern_c_destructor};
+/// # #[derive(Destruct)]
+/// # pub struct MyStruct {
+/// #    field: *mut std::ffi::c_char,
+/// # }
+/// // error: `free-my_struct` is not a valid function name
+/// extern_c_destructor!(MyStruct, prefix = "free-");
+/// ```
+///
+/// ## Multiple types
+/// Several types can be listed before the options, each getting its own destructor with the same
+/// options: `extern_c_destructor!(TypeA, TypeB, abi = "system")` generates `destruct_type_a()` and
+/// `destruct_type_b()`. `name = "..."` only applies to a single type.
+///
+/// ## Paths
+/// The type can be a path, named after its last segment:
+/// `extern_c_destructor!(crate::ffi::Structure)` generates
+/// `destruct_structure(ptr: *mut crate::ffi::Structure)`. Generic types, e.g. `List<u8>`, need
+/// `name = "..."`, since the symbol cannot embed the generic arguments.
+/// The same applies to [`extern_c_clone!`] and [`extern_c_constructor!`].
+///
+/// ## ABI
+/// `abi = "..."` sets the ABI of the function, `"C"` by default, e.g.
+/// `extern_c_destructor!(Structure, abi = "system")` for Windows APIs. The supported ABIs are
+/// `"C"`, `"C-unwind"`, `"system"`, `"system-unwind"`, `"cdecl"`, `"stdcall"`, `"fastcall"`,
+/// `"win64"`, `"sysv64"`, `"aapcs"` and `"efiapi"`, other strings are rejected at compile time.
+/// The target-specific ones, e.g. `"stdcall"`, only compile on the targets supporting them.
+///
+/// With `"C"`, a panic while dropping the structure aborts the process at the FFI boundary.
+/// Use `"C-unwind"` only if the callers are built to unwind through, e.g. C++ frames
+/// expecting Rust panics to propagate as foreign exceptions.
+///
+/// ## ABI version
+/// `abi_version = N` adds an `abi_version: u32` argument right after the pointer, e.g.
+/// `extern_c_destructor!(Structure, abi_version = 3)` generates
+/// `destruct_structure(ptr: *mut Structure, abi_version: u32)`.
+/// On mismatch, a C caller built against an incompatible layout of the structure,
+/// nothing is freed and a message is printed to stderr.
+///
+/// ## Hidden documentation
+/// `doc_hidden` adds `#[doc(hidden)]` to the function, e.g.
+/// `extern_c_destructor!(Structure, doc_hidden)`. The symbol is still exported for C,
+/// but it stays out of the rustdoc of crates serving Rust users as well.
+///
+/// ## Conditional compilation
+/// `cfg = "..."` only generates the function under the configuration predicate,
+/// e.g. `extern_c_destructor!(Structure, cfg = "unix")`.
+///
+/// Destructors cannot be generated lazily when the type is used, but the FFI surface of each type
+/// can be compiled out behind its own Cargo feature. Gate the `Drop` and each generated function,
+/// [`extern_c_constructor!`] and [`extern_c_clone!`] accept the same option:
+///
+/// ```toml
+/// [features]
+/// structure = []
+/// ```
+///
+/// ```
+/// # use ffi_destruct::{Destruct, extern_c_constructor, extern_c_destructor};
+/// #[derive(Destruct, Default)]
+/// #[destruct(cfg = "feature = \"structure\"")]
+/// pub struct Structure {
+///     #[nullable]
+///     field: *mut std::ffi::c_char,
+/// }
+///
+/// extern_c_constructor!(Structure, cfg = "feature = \"structure\"");
+/// extern_c_destructor!(Structure, cfg = "feature = \"structure\"");
+/// ```
+///
+/// ## Memory accounting
+/// `report(out = "...")` adds an out-parameter `*mut usize` receiving the number of freed objects:
+/// the structure itself, plus the `destruct_count()` of its fields,
+/// which requires `#[destruct(report)]` on the structure.
+/// An externally-sized array adds its `len` elements. A null out-parameter is ignored,
+/// and a null structure pointer reports `0`.
+///
+/// ```
+/// # use ffi_destruct::{Destruct, extern_c_destructor};
+/// #[derive(Destruct)]
+/// #[destruct(report)]
+/// pub struct Structure {
+///     #[array(len = "len")]
+///     data: *mut u8,
+///     len: usize,
+/// }
+///
+/// extern_c_destructor!(Structure, report(out = "freed_count"));
+/// // pub unsafe extern "C" fn destruct_structure(ptr: *mut Structure, freed_count: *mut usize)
+/// ```
+///
+/// ## Arrays of structures
+/// `array` generates `destruct_structure_array(ptr: *mut Structure, n: usize)` instead,
+/// freeing an array of `n` structures at once, e.g. returned by a list API.
+/// Each structure is dropped, then the array itself is freed.
+/// The array must be allocated as a `Vec` (or boxed slice) with equal length and capacity.
+///
+/// ```
+/// # use ffi_destruct::{Destruct, extern_c_destructor};
+/// # #[derive(Destruct)]
+/// # pub struct Structure {
+/// #    field: *mut std::ffi::c_char,
+/// # }
+/// extern_c_destructor!(Structure);
+/// extern_c_destructor!(Structure, array);
+/// // pub unsafe extern "C" fn destruct_structure_array(ptr: *mut Structure, n: usize)
+/// ```
+///
+/// ## Externally-sized arrays
+/// When the length of an array field is only known by the C side,
+/// `array(field = "...", elem = "...", len_arg)` adds a `len: usize` argument to the destructor.
+/// The pointer field named by `field` is reclaimed as a `Vec<elem>` of `len` elements
+/// (capacity must equal the length) before the structure is freed.
+/// The field must be marked `#[no_drop]`, so that the structure's own destructor skips it.
+///
+/// ```
+/// # use ffi_destruct::{Destruct, extern_c_destructor};
+/// #[derive(Destruct)]
+/// pub struct Buffer {
+///     #[no_drop]
+///     data: *mut f32,
+/// }
+///
+/// extern_c_destructor!(Buffer, array(field = "data", elem = "f32", len_arg));
+/// // pub unsafe extern "C" fn destruct_buffer(ptr: *mut Buffer, len: usize)
+/// ```
+///
+/// ## Status result
+/// With `result`, the function returns the `DestructResult` generated by [`destruct_result!`],
+/// which must be in scope: `NullPtr` if the pointer is null, `PanicCaught` if a drop panicked,
+/// and `Ok` otherwise. The panic is caught with `catch_unwind`, instead of unwinding into C.
+/// Cannot be combined with `abi_version` or `returns_bool`.
+///
+/// ```
+/// # use ffi_destruct::{destruct_result, extern_c_destructor, Destruct};
+/// #[derive(Destruct)]
+/// pub struct Structure {
+///     field: *mut std::ffi::c_char,
+/// }
+///
+/// destruct_result!();
+/// extern_c_destructor!(Structure, result);
+/// // pub unsafe extern "C" fn destruct_structure(ptr: *mut Structure) -> DestructResult
+///
+/// let result = unsafe { destruct_structure(std::ptr::null_mut()) };
+/// assert_eq!(result, DestructResult::NullPtr);
+/// ```
+///
+/// ## Boolean result
+/// With `returns_bool`, the function returns a `bool`: `true` if a structure was freed, and
+/// `false` if the pointer is null, or the ABI version does not match with `abi_version`.
+/// C callers nulling their pointers after freeing can detect double-free attempts.
+///
+/// ```
+/// # use ffi_destruct::{extern_c_destructor, Destruct};
+/// # #[derive(Destruct)]
+/// # pub struct Structure {
+/// #     field: *mut std::ffi::c_char,
+/// # }
+/// extern_c_destructor!(Structure, returns_bool);
+/// // pub unsafe extern "C" fn destruct_structure(ptr: *mut Structure) -> bool
+///
+/// assert!(!unsafe { destruct_structure(std::ptr::null_mut()) });
+/// ```
+///
+/// ## Checked
+/// With `checked`, the function is named with a `_checked` suffix and returns a C status code:
+/// `0` if a structure was freed, `-1` if the pointer is null, and `-2` if the ABI version does not
+/// match with `abi_version`. The form without `checked` is unchanged, and both can be generated.
+///
+/// ```
+/// # use ffi_destruct::{extern_c_destructor, Destruct};
+/// # #[derive(Destruct)]
+/// # pub struct Structure {
+/// #     field: *mut std::ffi::c_char,
+/// # }
+/// extern_c_destructor!(Structure, checked);
+/// // pub unsafe extern "C" fn destruct_structure_checked(ptr: *mut Structure) -> c_int
+///
+/// assert_eq!(unsafe { destruct_structure_checked(std::ptr::null_mut()) }, -1);
+/// ```
 #[proc_macro]
 pub fn extern_c_destructor(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let ty: syn::Type = parse_macro_input!(input);
-    match ty {
-        syn::Type::Path(v) => {
-            let ident = v.path.get_ident().expect("Only support single ident.");
-            let mut name = ident.to_string().to_case(Case::Snake);
-            name.insert_str(0, "destruct_");
-            let fn_ident = Ident::new(&name, ident.span());
-            quote! {
-                #[no_mangle]
-                pub unsafe extern "C" fn #fn_ident(ptr: *mut #ident) {
-                    if ptr.is_null() {
-                        return;
-                    }
-                    let _ = ::std::boxed::Box::from_raw(ptr);
-                }
-            }
-            .into()
-        }
-        _ => panic!("Not supported type"),
-    }
+    let input = parse_macro_input!(input as extern_c::Destructor);
+
+    let expand = extern_c::impl_extern_c_destructor(&input);
+
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
+}
+
+/// Generate extern "C" constructor for provide type
+///
+/// Provide the function name: "new_" + snake_case name of the type,
+/// or `name = "..."` verbatim as with [`extern_c_destructor!`].
+/// The structure is boxed and returned as a raw pointer, for the destructor of
+/// [`extern_c_destructor!`] to free, giving C callers a symmetric allocation and free pair.
+///
+/// ## Usage
+///
+/// ```
+/// # use ffi_destruct::{Destruct, extern_c_constructor, extern_c_destructor};
+/// #[derive(Destruct, Default)]
+/// pub struct MyStruct {
+///     #[nullable]
+///     field: *mut std::ffi::c_char,
+/// }
+///
+/// extern_c_constructor!(MyStruct);
+/// extern_c_destructor!(MyStruct);
+/// // pub unsafe extern "C" fn new_my_struct() -> *mut MyStruct
+///
+/// unsafe { destruct_my_struct(new_my_struct()) };
+/// ```
+///
+/// The structure is constructed with `Default::default()`, so the type must implement `Default`.
+/// `default` states it explicitly: `extern_c_constructor!(MyStruct, default)`.
+///
+/// With `zeroed`, the structure is allocated with `std::mem::zeroed()` instead, e.g. for
+/// structures without `Default` which C fills in after allocation. All-zero bytes must be a valid
+/// value of every field, and pointer fields are null, so they must be `#[nullable]` unless C sets
+/// them before the structure is freed.
+///
+/// `cfg = "..."` only generates the function under the configuration predicate, see
+/// [Conditional compilation](macro@extern_c_destructor#conditional-compilation).
+///
+/// With the `gauge` feature, the constructor increments the `live_gauge()` of the type,
+/// which must derive [`Destruct`].
+#[proc_macro]
+pub fn extern_c_constructor(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as extern_c::Constructor);
+
+    let expand = extern_c::impl_extern_c_constructor(&input);
+
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
+}
+
+/// Generate the `DestructResult` enum returned by the destructors with the `result` option
+///
+/// Invoke it once per crate, e.g. at the crate root, and import it where the destructors are
+/// generated. It is `#[repr(C)]` with fixed discriminants, a stable ABI declared in C as:
+///
+/// ```c
+/// typedef enum DestructResult {
+///     DESTRUCT_RESULT_OK = 0,
+///     DESTRUCT_RESULT_NULL_PTR = 1,
+///     DESTRUCT_RESULT_PANIC_CAUGHT = 2,
+/// } DestructResult;
+/// ```
+///
+/// Like a C enum, it has the size of an `int` on the target. See
+/// [Status result](macro@extern_c_destructor#status-result).
+#[proc_macro]
+pub fn destruct_result(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    parse_macro_input!(input as syn::parse::Nothing);
+
+    let expand = extern_c::impl_destruct_result();
+
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
+}
+
+/// Generate the `Destructible` trait implemented with `#[destruct(destructible)]`
+///
+/// Invoke it once per crate, e.g. at the crate root, and import it where the structures are
+/// derived. `unsafe fn free_raw(ptr: *mut Self)` frees a structure allocated with `Box`, and
+/// nothing if the pointer is null, so generic code can free any of the structures.
+///
+/// ```
+/// # use ffi_destruct::{destructible, Destruct};
+/// destructible!();
+///
+/// #[derive(Destruct)]
+/// #[destruct(destructible)]
+/// pub struct Structure {
+///     c_string: *mut std::ffi::c_char,
+/// }
+///
+/// unsafe fn free_all<T: Destructible>(ptrs: &[*mut T]) {
+///     for &ptr in ptrs {
+///         T::free_raw(ptr);
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn destructible(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    parse_macro_input!(input as syn::parse::Nothing);
+
+    let expand = destruct::impl_destructible_trait();
+
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
+}
+
+/// Generate the `gauge` module, the registry of the live objects of every type of the crate
+///
+/// Invoke it once at the crate root. With the `gauge` feature, the `live_gauge()` of each type
+/// marked `#[destruct(gauge)]` registers itself under the path of the type on first use, e.g. by
+/// the constructor of [`extern_c_constructor!`]. `gauge::live("my_crate::Structure")` returns the
+/// live objects of the type, `None` until then, and `gauge::snapshot()` those of every registered
+/// type, e.g. for a dashboard. No other item of the crate root may be named `gauge`.
+///
+/// ```
+/// # use ffi_destruct::{gauge_registry, Destruct};
+/// gauge_registry!();
+///
+/// #[derive(Destruct)]
+/// #[destruct(gauge)]
+/// pub struct Structure {
+///     c_string: *mut std::ffi::c_char,
+/// }
+///
+/// fn main() {
+///     for (name, live) in gauge::snapshot() {
+///         println!("{}: {}", name, live);
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn gauge_registry(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    parse_macro_input!(input as syn::parse::Nothing);
+
+    let expand = destruct::impl_gauge_registry();
+
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
+}
+
+/// Generate the `fuzz` module logging the addresses freed by every type of the crate
+///
+/// Invoke it once at the crate root. With the `fuzz` feature, the destructors of every type marked
+/// `#[destruct(fuzz)]` record each freed address with `fuzz::record_free(addr)`, and
+/// `fuzz::freed_addresses()` returns the log in the order of the frees, across types. A harness
+/// can record the frees of C code with `fuzz::record_free` too. No other item of the crate root
+/// may be named `fuzz`.
+///
+/// ```
+/// # use ffi_destruct::{fuzz_log, Destruct};
+/// fuzz_log!();
+///
+/// #[derive(Destruct)]
+/// #[destruct(fuzz)]
+/// pub struct Structure {
+///     c_string: *mut std::ffi::c_char,
+/// }
+///
+/// fn main() {
+///     // Nothing is freed yet
+///     assert!(fuzz::freed_addresses().lock().unwrap().is_empty());
+/// }
+/// ```
+#[proc_macro]
+pub fn fuzz_log(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    parse_macro_input!(input as syn::parse::Nothing);
+
+    let expand = destruct::impl_fuzz_log_module();
+
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
+}
+
+/// Generate extern "C" deep-clone function for provide type
+///
+/// Provide the function name: "clone_" + snake_case name of the type,
+/// or `name = "..."` verbatim as with [`extern_c_destructor!`].
+/// The clone is boxed, so the destructor of [`extern_c_destructor!`] frees it.
+///
+/// The type must implement `Clone` as a deep copy, allocating its own pointees.
+/// A derived `Clone` copies the raw pointers, and freeing both objects would double free.
+///
+/// `cfg = "..."` only generates the function under the configuration predicate, see
+/// [Conditional compilation](macro@extern_c_destructor#conditional-compilation).
+///
+/// ## Usage
+///
+/// ```
+/// # use ffi_destruct::{Destruct, extern_c_clone, extern_c_destructor};
+/// # use std::ffi::{c_char, CStr};
+/// #[derive(Destruct)]
+/// pub struct MyStruct {
+///     field: *mut c_char,
+/// }
+///
+/// impl Clone for MyStruct {
+///     fn clone(&self) -> Self {
+///         let field = unsafe { CStr::from_ptr(self.field) };
+///         MyStruct {
+///             field: field.to_owned().into_raw(),
+///         }
+///     }
+/// }
+///
+/// extern_c_clone!(MyStruct);
+/// extern_c_destructor!(MyStruct);
+/// // pub unsafe extern "C" fn clone_my_struct(ptr: *const MyStruct) -> *mut MyStruct
+/// ```
+/// A null pointer is cloned as null.
+#[proc_macro]
+pub fn extern_c_clone(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as extern_c::Cloner);
+
+    let expand = extern_c::impl_extern_c_clone(&input);
+
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
+}
+
+/// Generate the `atexit`-compatible destructor of a global
+///
+/// The global is a `static AtomicPtr<T>`, null or holding a pointer from `Box::into_raw`.
+/// Provide the function `extern "C" fn()`: "atexit_" + snake_case name of the global,
+/// which frees the object and leaves the global null.
+///
+/// ```
+/// # use ffi_destruct::{atexit_destructor, Destruct};
+/// # use std::sync::atomic::AtomicPtr;
+/// #[derive(Destruct)]
+/// pub struct Structure {
+///     field: *mut std::ffi::c_char,
+/// }
+///
+/// static GLOBAL_STRUCT: AtomicPtr<Structure> = AtomicPtr::new(std::ptr::null_mut());
+///
+/// atexit_destructor!(GLOBAL_STRUCT);
+/// // pub extern "C" fn atexit_global_struct()
+/// ```
+///
+/// Register it with the `atexit` of the C library while initializing the global,
+/// e.g. `libc::atexit(atexit_global_struct)`.
+/// With the `libc` feature, `fn register_atexit_global_struct() -> bool` is generated as well,
+/// registering it once with the `atexit` of the C library, declared by the generated code.
+#[proc_macro]
+pub fn atexit_destructor(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::Path);
+
+    let expand = extern_c::impl_atexit_destructor(&input);
+
+    proc_macro::TokenStream::from(utils::reroute_std(expand))
 }
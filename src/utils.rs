@@ -1,10 +1,696 @@
-/// Check if the attribute exist.
-pub fn get_attribute(attrs: &Vec<syn::Attribute>, ident: &str) -> bool {
-    let mut exist = false;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+
+/// Reroute the `::std` paths of the generated code to `::core` without the `std` feature, and to
+/// `::alloc` with the `alloc` feature, e.g. `::std::ops::Drop` to `::core::ops::Drop` and
+/// `::std::boxed::Box` to `::alloc::boxed::Box`.
+///
+/// Paths only available in `std`, e.g. `::std::thread` or `::std::sync::OnceLock`, are kept, and so
+/// are the paths of `alloc` without the `alloc` feature, since only a crate declaring
+/// `extern crate alloc` can name it.
+pub fn reroute_std(tokens: TokenStream) -> TokenStream {
+    use proc_macro2::{Group, Ident, Spacing, TokenTree};
+
+    /// The crate re-exporting the `std` item at the path.
+    fn krate(module: &str, item: Option<&str>) -> Option<&'static str> {
+        let alloc = cfg!(feature = "alloc").then_some("alloc");
+        match (module, item) {
+            ("boxed" | "vec" | "string" | "collections", _) => alloc,
+            ("ffi", Some("CString")) => alloc,
+            ("sync", Some("atomic")) => Some("core"),
+            ("sync", _) => None,
+            ("mem" | "ptr" | "ops" | "marker" | "default" | "result" | "option" | "clone", _) => {
+                Some("core")
+            }
+            ("ffi" | "cell" | "fmt" | "any" | "stringify", _) => Some("core"),
+            _ => None,
+        }
+    }
+    /// Whether the tokens are the `::` path separator.
+    fn is_separator(tokens: &[TokenTree]) -> bool {
+        matches!(
+            tokens,
+            [TokenTree::Punct(first), TokenTree::Punct(second), ..]
+                if first.as_char() == ':'
+                    && first.spacing() == Spacing::Joint
+                    && second.as_char() == ':'
+        )
+    }
+
+    if cfg!(feature = "std") {
+        return tokens;
+    }
+    let tokens = tokens.into_iter().collect::<Vec<_>>();
+    let mut output = Vec::with_capacity(tokens.len());
+    for (index, token) in tokens.iter().enumerate() {
+        let token = match *token {
+            TokenTree::Group(ref group) => {
+                let mut rerouted = Group::new(group.delimiter(), reroute_std(group.stream()));
+                rerouted.set_span(group.span());
+                TokenTree::Group(rerouted)
+            }
+            // `::std::module::item`
+            TokenTree::Ident(ref ident)
+                if ident == "std"
+                    && index >= 2
+                    && is_separator(&tokens[index - 2..])
+                    && is_separator(&tokens[index + 1..]) =>
+            {
+                let module = match tokens.get(index + 3) {
+                    Some(TokenTree::Ident(module)) => module.to_string(),
+                    _ => String::new(),
+                };
+                let item = match tokens.get(index + 6) {
+                    Some(TokenTree::Ident(item)) if is_separator(&tokens[index + 4..index + 6]) => {
+                        Some(item.to_string())
+                    }
+                    _ => None,
+                };
+                match krate(&module, item.as_deref()) {
+                    Some(krate) => TokenTree::Ident(Ident::new(krate, ident.span())),
+                    None => token.clone(),
+                }
+            }
+            _ => token.clone(),
+        };
+        output.push(token);
+    }
+    output.into_iter().collect()
+}
+
+/// What a helper attribute applies to, checked on the fields.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// Only supported for raw pointer fields.
+    PointerOnly,
+    /// Changes how the pointer is freed, so it cannot be combined with `#[no_drop]`.
+    IgnoredByNoDrop,
+    /// A way of freeing the pointer, only one applies.
+    FreeMethod,
+}
+
+/// An entry of the helper attribute table.
+pub struct HelperAttribute {
+    pub name: &'static str,
+    pub kinds: &'static [Kind],
+}
+
+/// The helper attributes of the derive with their kinds, in the order of its `attributes(...)`.
+///
+/// Keep in sync with the `#[proc_macro_derive]` of `Destruct` in `lib.rs`. The kinds are checked
+/// on the canonical names, e.g. `array` for `vec`.
+pub const HELPER_ATTRIBUTES: &[HelperAttribute] = &[
+    HelperAttribute {
+        name: "nullable",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "non_null",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "no_drop",
+        kinds: &[],
+    },
+    HelperAttribute {
+        name: "owned",
+        kinds: &[Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "drop_if",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "owned_if",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "flush_before_drop",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "pre_drop",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "pre_free",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "alias_of",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "init_guard",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "interned",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "free_if_callback",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "cfg_not_miri_skip",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "drop_order",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop],
+    },
+    HelperAttribute {
+        name: "erased",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop, Kind::FreeMethod],
+    },
+    HelperAttribute {
+        name: "reclaim",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop, Kind::FreeMethod],
+    },
+    HelperAttribute {
+        name: "ptr",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop, Kind::FreeMethod],
+    },
+    HelperAttribute {
+        name: "drop_with",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop, Kind::FreeMethod],
+    },
+    HelperAttribute {
+        name: "array",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop, Kind::FreeMethod],
+    },
+    HelperAttribute {
+        name: "vec",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop, Kind::FreeMethod],
+    },
+    HelperAttribute {
+        name: "byte_string",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop, Kind::FreeMethod],
+    },
+    HelperAttribute {
+        name: "wide",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop, Kind::FreeMethod],
+    },
+    HelperAttribute {
+        name: "c_string",
+        kinds: &[Kind::PointerOnly, Kind::IgnoredByNoDrop, Kind::FreeMethod],
+    },
+    HelperAttribute {
+        name: "union_active",
+        kinds: &[],
+    },
+    HelperAttribute {
+        name: "release_via",
+        kinds: &[],
+    },
+    HelperAttribute {
+        name: "destruct",
+        kinds: &[],
+    },
+];
+
+/// Get the string value of a `#[ident = "..."]` attribute.
+pub fn get_attribute_value(
+    attrs: &[syn::Attribute],
+    ident: &str,
+) -> syn::Result<Option<syn::LitStr>> {
+    attrs
+        .iter()
+        .find(|attr| attr.path().is_ident(ident))
+        .map(|attr| attribute_value(attr, ident))
+        .transpose()
+}
+
+/// The string value of the `#[ident = "..."]` attribute.
+fn attribute_value(attr: &syn::Attribute, ident: &str) -> syn::Result<syn::LitStr> {
+    match attr.meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            value:
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(ref value),
+                    ..
+                }),
+            ..
+        }) => Ok(value.clone()),
+        _ => {
+            let message = format!("Expected attribute of the form #[{} = \"...\"]", ident);
+            Err(syn::Error::new_spanned(attr, message))
+        }
+    }
+}
+
+/// Prefix the message of a parse error, keeping its span.
+fn invalid(err: syn::Error, what: impl std::fmt::Display) -> syn::Error {
+    syn::Error::new(err.span(), format!("Invalid {}: {}", what, err))
+}
+
+/// Options of the `#[array(...)]` field attribute.
+#[derive(Clone)]
+pub struct ArrayAttribute {
+    /// `len = "..."` or `len = N`: The number of elements.
+    pub len: TokenStream,
+    /// `require_drop`: Assert the elements need dropping.
+    pub require_drop: bool,
+    /// `cap = "..."`: The capacity of the `Vec` for `#[vec(...)]`, the length otherwise.
+    pub cap: Option<TokenStream>,
+}
+
+/// Parse the `#[array(...)]` attribute, `#[vec(...)]` adding its `cap`, or `#[byte_string(...)]`
+/// sharing its `len`.
+///
+/// A field name as `len` is read from `self`, otherwise the length is any expression, or an integer
+/// literal for a constant length.
+fn parse_array_attribute(attr: &syn::Attribute, ident: &str) -> syn::Result<ArrayAttribute> {
+    let mut len = None;
+    let mut cap = None;
+    let mut require_drop = false;
+    let result = attr.parse_nested_meta(|meta| {
+        let value = if meta.path.is_ident("len") {
+            &mut len
+        } else if meta.path.is_ident("cap") && ident == "vec" {
+            &mut cap
+        } else if meta.path.is_ident("require_drop") && ident != "byte_string" {
+            require_drop = true;
+            return Ok(());
+        } else {
+            return Err(meta.error(format!("unsupported {} attribute", ident)));
+        };
+        *value = Some(match meta.value()?.parse()? {
+            // A constant length, e.g. `len = 256`
+            syn::Lit::Int(value) => {
+                value.base10_parse::<usize>()?;
+                value.into_token_stream()
+            }
+            syn::Lit::Str(value) => match value.parse::<syn::Ident>() {
+                Ok(field) => quote::quote! { self.#field },
+                Err(_) => value.parse::<syn::Expr>()?.into_token_stream(),
+            },
+            value => return Err(syn::Error::new_spanned(value, "expected string or integer")),
+        });
+        Ok(())
+    });
+    result.map_err(|err| invalid(err, format_args!("{} attribute", ident)))?;
+    if ident == "vec" && cap.is_none() {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "vec attribute requires `cap`",
+        ));
+    }
+    let Some(len) = len else {
+        let message = format!("{} attribute requires `len`", ident);
+        return Err(syn::Error::new_spanned(attr, message));
+    };
+    Ok(ArrayAttribute {
+        len,
+        require_drop,
+        cap,
+    })
+}
+
+/// Helper attributes of a field.
+#[derive(Default)]
+pub struct FieldAttributes {
+    pub nullable: bool,
+    /// `#[nullable(sentinel = "...")]`: The invalid pointer skipped like null.
+    pub sentinel: Option<syn::Expr>,
+    /// `#[non_null]`: Opt out of the `#[destruct(nullable)]` default.
+    pub non_null: bool,
+    pub no_drop: bool,
+    pub owned: bool,
+    pub drop_if: Option<syn::Expr>,
+    pub owned_if: Option<syn::Ident>,
+    pub reclaim: Option<syn::Type>,
+    /// `#[ptr(box = "...")]`: The box type of the pointee, e.g. `RBox` for a `RBox<T>`.
+    pub ptr_box: Option<syn::Path>,
+    /// `#[drop_with = "..."]`: The function freeing the pointer, e.g. `libc::free`.
+    pub drop_with: Option<syn::Path>,
+    pub array: Option<ArrayAttribute>,
+    /// `#[byte_string(len = "...")]`: The length of the non-NUL-terminated string.
+    pub byte_string: Option<ArrayAttribute>,
+    /// `#[wide]`: The NUL-terminated UTF-16 string.
+    pub wide: bool,
+    /// `#[c_string]`: The `CString` of any byte-sized element type.
+    pub c_string: bool,
+    pub flush_before_drop: Option<syn::Ident>,
+    /// `#[pre_drop = "..."]`: The method called on the pointee before freeing it.
+    pub pre_drop: Option<syn::Ident>,
+    pub pre_free: bool,
+    pub alias_of: Option<syn::Ident>,
+    pub init_guard: Option<syn::Ident>,
+    pub interned: bool,
+    /// `#[interned = "..."]`: The lookup of the intern registry.
+    pub interned_registry: Option<syn::Path>,
+    pub free_if_callback: Option<syn::Ident>,
+    pub cfg_not_miri_skip: bool,
+    /// `#[union_active]`: The live field of a union.
+    pub union_active: bool,
+    /// `#[drop_order(N)]`: The priority of the field, lower first.
+    pub drop_order: i32,
+    /// `#[erased(drop_fn = "...")]`: The field holding the drop function of the erased pointee.
+    pub erased: Option<syn::Ident>,
+    /// The canonical names of the helper attributes of the field, in declaration order.
+    pub attributes: Vec<String>,
+    /// The helper attributes repeated on the field, after their first occurrence.
+    pub duplicates: Vec<syn::Ident>,
+}
+
+/// Parse the helper attributes of a field in a single pass.
+///
+/// Each attribute is only matched once by name, so deriving on structures with thousands of
+/// fields stays linear.
+pub fn get_field_attributes(attrs: &[syn::Attribute]) -> syn::Result<FieldAttributes> {
+    /// Parse the string value of a `#[ident = "..."]` attribute.
+    fn parse_value<T: syn::parse::Parse>(
+        attr: &syn::Attribute,
+        ident: &str,
+        what: &str,
+    ) -> syn::Result<T> {
+        let value = attribute_value(attr, ident)?;
+        value
+            .parse()
+            .map_err(|err| invalid(err, format_args!("{} {}", ident, what)))
+    }
+
+    let mut options = FieldAttributes::default();
+    for attr in attrs {
+        // Doc comments and other outer attributes are skipped without comparing names
+        let Some(ident) = attr.path().get_ident() else {
+            continue;
+        };
+        let name = ident.to_string();
+        if HELPER_ATTRIBUTES.iter().any(|helper| helper.name == name) {
+            // Aliases count as the attribute they alias
+            let canonical = match name.as_str() {
+                "vec" => "array".to_string(),
+                _ => name.clone(),
+            };
+            if options.attributes.contains(&canonical) {
+                options.duplicates.push(ident.clone());
+            } else {
+                options.attributes.push(canonical);
+            }
+        }
+        match name.as_str() {
+            "nullable" if !options.nullable => {
+                options.nullable = true;
+                if let syn::Meta::List(_) = attr.meta {
+                    let result = attr.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("sentinel") {
+                            let value: syn::LitStr = meta.value()?.parse()?;
+                            options.sentinel = Some(value.parse()?);
+                            Ok(())
+                        } else {
+                            Err(meta.error("unsupported nullable attribute"))
+                        }
+                    });
+                    result.map_err(|err| invalid(err, "nullable attribute"))?;
+                }
+            }
+            "non_null" => options.non_null = true,
+            "no_drop" => options.no_drop = true,
+            "owned" => options.owned = true,
+            "pre_free" => options.pre_free = true,
+            "wide" => options.wide = true,
+            "c_string" => options.c_string = true,
+            "cfg_not_miri_skip" => options.cfg_not_miri_skip = true,
+            "union_active" => options.union_active = true,
+            "interned" if !options.interned => {
+                options.interned = true;
+                if let syn::Meta::NameValue(_) = attr.meta {
+                    options.interned_registry = Some(parse_value(attr, "interned", "registry")?);
+                }
+            }
+            // The first occurrence wins
+            "drop_if" if options.drop_if.is_none() => {
+                options.drop_if = Some(parse_value(attr, "drop_if", "expression")?);
+            }
+            "owned_if" if options.owned_if.is_none() => {
+                options.owned_if = Some(parse_value(attr, "owned_if", "field")?);
+            }
+            "reclaim" if options.reclaim.is_none() => {
+                options.reclaim = Some(parse_value(attr, "reclaim", "type")?);
+            }
+            "ptr" if options.ptr_box.is_none() => {
+                let result = attr.parse_nested_meta(|meta| {
+                    if !meta.path.is_ident("box") {
+                        return Err(meta.error("unsupported ptr attribute"));
+                    }
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    let path: syn::Path = value.parse()?;
+                    if path.segments.iter().any(|s| !s.arguments.is_none()) {
+                        let message = "expected the box type without arguments, e.g. `RBox`";
+                        return Err(syn::Error::new_spanned(value, message));
+                    }
+                    options.ptr_box = Some(path);
+                    Ok(())
+                });
+                result.map_err(|err| invalid(err, "ptr attribute"))?;
+                if options.ptr_box.is_none() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "ptr attribute requires `box`",
+                    ));
+                }
+            }
+            "drop_with" if options.drop_with.is_none() => {
+                options.drop_with = Some(parse_value(attr, "drop_with", "function")?);
+            }
+            "flush_before_drop" if options.flush_before_drop.is_none() => {
+                options.flush_before_drop = Some(parse_value(attr, "flush_before_drop", "method")?);
+            }
+            "pre_drop" if options.pre_drop.is_none() => {
+                options.pre_drop = Some(parse_value(attr, "pre_drop", "method")?);
+            }
+            "alias_of" if options.alias_of.is_none() => {
+                options.alias_of = Some(parse_value(attr, "alias_of", "field")?);
+            }
+            "init_guard" if options.init_guard.is_none() => {
+                options.init_guard = Some(parse_value(attr, "init_guard", "field")?);
+            }
+            "free_if_callback" if options.free_if_callback.is_none() => {
+                options.free_if_callback = Some(parse_value(attr, "free_if_callback", "field")?);
+            }
+            "drop_order" => {
+                let parser = |input: syn::parse::ParseStream| {
+                    let negative = input.parse::<Option<syn::Token![-]>>()?.is_some();
+                    let order = input.parse::<syn::LitInt>()?.base10_parse::<i32>()?;
+                    Ok(if negative { -order } else { order })
+                };
+                options.drop_order = attr
+                    .parse_args_with(parser)
+                    .map_err(|err| invalid(err, "drop_order priority"))?;
+            }
+            "erased" if options.erased.is_none() => {
+                let mut drop_fn = None;
+                let result = attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("drop_fn") {
+                        let value: syn::LitStr = meta.value()?.parse()?;
+                        drop_fn = Some(value.parse()?);
+                        Ok(())
+                    } else {
+                        Err(meta.error("unsupported erased attribute"))
+                    }
+                });
+                result.map_err(|err| invalid(err, "erased attribute"))?;
+                let Some(drop_fn) = drop_fn else {
+                    let message = "Erased attribute requires `drop_fn`";
+                    return Err(syn::Error::new_spanned(attr, message));
+                };
+                options.erased = Some(drop_fn);
+            }
+            "array" | "vec" if options.array.is_none() => {
+                options.array = Some(parse_array_attribute(attr, &name)?);
+            }
+            "byte_string" if options.byte_string.is_none() => {
+                options.byte_string = Some(parse_array_attribute(attr, "byte_string")?);
+            }
+            _ => {}
+        }
+    }
+    Ok(options)
+}
+
+impl FieldAttributes {
+    /// Whether the pointer is null-checked, explicitly or by the `#[destruct(nullable)]` default.
+    pub fn is_nullable(&self, options: &ContainerAttributes) -> bool {
+        self.nullable || (options.nullable && !self.non_null)
+    }
+}
+
+/// Remove the helper attributes of the derive.
+pub fn strip_helper_attributes(attrs: &mut Vec<syn::Attribute>) {
+    attrs.retain(|attr| {
+        !HELPER_ATTRIBUTES
+            .iter()
+            .any(|helper| attr.path().is_ident(helper.name))
+    });
+}
+
+/// Options of the container-level `#[destruct(...)]` attribute.
+#[derive(Default)]
+pub struct ContainerAttributes {
+    /// `cfg = "..."`: The configuration predicate the destructor is generated under.
+    pub cfg: Option<TokenStream>,
+    /// `iterative`: Free the tree of descendants with a work-stack instead of recursion.
+    pub iterative: bool,
+    /// `child = "..."`: The field holding the array of child pointers.
+    pub child: Option<syn::Ident>,
+    /// `len = "..."`: The field holding the number of children.
+    pub len: Option<syn::Ident>,
+    /// `nullable`: Null-check every pointer field unless marked `#[non_null]`.
+    pub nullable: bool,
+    /// `pod_ok`: Skip generating `Drop` if there is nothing to free.
+    pub pod_ok: bool,
+    /// `require_repr_c`: Reject structures without `#[repr(C)]` or `#[repr(transparent)]`.
+    pub require_repr_c: bool,
+    /// `strict`: Reject the fields of unknown types which are not marked `#[no_drop]`.
+    pub strict: bool,
+    /// `report`: Generate `destruct_count()`.
+    pub report: bool,
+    /// `gauge`: Register `live_gauge()` in the registry of `gauge_registry!()`.
+    pub gauge: bool,
+    /// `fuzz`: Record the freed addresses in the log of `fuzz_log!()`.
+    pub fuzz: bool,
+    /// `refcount = "..."`: The `AtomicUsize` field counting the references.
+    pub refcount: Option<syn::Ident>,
+    /// `canary = "..."`: The field which must equal `value` to be destructed.
+    pub canary: Option<syn::Ident>,
+    /// `value = "..."`: The expected value of the canary.
+    pub value: Option<syn::Expr>,
+    /// `skip_if_poisoned = "..."`: The `bool` field marking broken invariants.
+    pub skip_if_poisoned: Option<syn::Ident>,
+    /// `notify_sender = "..."`: The sender field notified after freeing.
+    pub notify_sender: Option<syn::Ident>,
+    /// `consumed_flag = "..."`: The `bool` field marking the ownership as transferred.
+    pub consumed_flag: Option<syn::Ident>,
+    /// `with = "..."`: The function freeing the whole structure instead of the fields.
+    pub with: Option<syn::Path>,
+    /// `builder`: Generate a builder freeing the fields set so far.
+    pub builder: bool,
+    /// `manual`: Generate `destruct(self)` for explicit cleanup.
+    pub manual: bool,
+    /// `destructible`: Implement the `Destructible` trait of `destructible!()`.
+    pub destructible: bool,
+    /// `defer`: Free the fields on a background thread.
+    pub defer: bool,
+    /// `zero_on_drop`: Null out each pointer field after freeing it.
+    pub zero_on_drop: bool,
+    /// `log = "..."`: The `log` level of the message at the start of `drop`.
+    pub log: Option<syn::Ident>,
+    /// `#[release_via = "..."]`: The vtable pointer and its release function field.
+    pub release_via: Option<(syn::Expr, syn::Ident)>,
+}
+
+/// The levels of the `log` crate, named after their logging macros.
+const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Parse the container-level `#[destruct(...)]` attributes.
+pub fn get_container_attributes(attrs: &[syn::Attribute]) -> syn::Result<ContainerAttributes> {
+    let mut options = ContainerAttributes::default();
     for attr in attrs {
-        if attr.path().is_ident(ident) {
-            exist = true;
+        if !attr.path().is_ident("destruct") {
+            continue;
         }
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("cfg") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.cfg = Some(value.parse()?);
+            } else if meta.path.is_ident("refcount") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.refcount = Some(value.parse()?);
+            } else if meta.path.is_ident("canary") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.canary = Some(value.parse()?);
+            } else if meta.path.is_ident("value") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.value = Some(value.parse()?);
+            } else if meta.path.is_ident("skip_if_poisoned") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.skip_if_poisoned = Some(value.parse()?);
+            } else if meta.path.is_ident("notify_sender") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.notify_sender = Some(value.parse()?);
+            } else if meta.path.is_ident("consumed_flag") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.consumed_flag = Some(value.parse()?);
+            } else if meta.path.is_ident("with") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.with = Some(value.parse()?);
+            } else if meta.path.is_ident("log") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                let level: syn::Ident = value.parse()?;
+                if !LOG_LEVELS.contains(&level.to_string().as_str()) {
+                    return Err(
+                        meta.error("expected a `log` level: error, warn, info, debug or trace")
+                    );
+                }
+                options.log = Some(level);
+            } else if meta.path.is_ident("report") {
+                options.report = true;
+            } else if meta.path.is_ident("gauge") {
+                options.gauge = true;
+            } else if meta.path.is_ident("fuzz") {
+                options.fuzz = true;
+            } else if meta.path.is_ident("builder") {
+                options.builder = true;
+            } else if meta.path.is_ident("manual") {
+                options.manual = true;
+            } else if meta.path.is_ident("destructible") {
+                options.destructible = true;
+            } else if meta.path.is_ident("defer") {
+                options.defer = true;
+            } else if meta.path.is_ident("nullable") {
+                options.nullable = true;
+            } else if meta.path.is_ident("zero_on_drop") {
+                options.zero_on_drop = true;
+            } else if meta.path.is_ident("require_repr_c") {
+                options.require_repr_c = true;
+            } else if meta.path.is_ident("strict") {
+                options.strict = true;
+            } else if meta.path.is_ident("pod_ok") {
+                options.pod_ok = true;
+            } else if meta.path.is_ident("iterative") {
+                options.iterative = true;
+            } else if meta.path.is_ident("child") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.child = Some(value.parse()?);
+            } else if meta.path.is_ident("len") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                options.len = Some(value.parse()?);
+            } else {
+                return Err(meta.error("unsupported destruct attribute"));
+            }
+            Ok(())
+        });
+        result.map_err(|err| invalid(err, "destruct attribute"))?;
+    }
+    if let Some(value) = get_attribute_value(attrs, "release_via")? {
+        let parser = |input: syn::parse::ParseStream| {
+            let vtable = input.parse()?;
+            input.parse::<syn::Token![,]>()?;
+            let release = input.parse()?;
+            Ok((vtable, release))
+        };
+        let release_via = value
+            .parse_with(parser)
+            .map_err(|err| invalid(err, "release_via attribute"))?;
+        options.release_via = Some(release_via);
+    }
+    // The conflicting options are reported at the first `#[destruct(...)]` attribute
+    let destruct = attrs.iter().find(|attr| attr.path().is_ident("destruct"));
+    let error = |message: &str| Err(syn::Error::new_spanned(destruct, message));
+    if options.canary.is_some() != options.value.is_some() {
+        return error("Canary requires both `canary` and `value`");
+    }
+    if options.builder && options.iterative {
+        return error("Builder cannot be combined with `iterative`");
+    }
+    if options.defer && (options.release_via.is_some() || options.notify_sender.is_some()) {
+        return error("Deferred destruct cannot be combined with `release_via` or `notify_sender`");
+    }
+    if options.iterative && (options.child.is_none() || options.len.is_none()) {
+        return error("Iterative destruct requires both `child` and `len`");
     }
-    exist
+    Ok(options)
 }
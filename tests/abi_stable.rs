@@ -0,0 +1,87 @@
+#![allow(dead_code, unused)]
+
+//! Stand-ins mimicking the pointer wrappers of ABI-stability crates like `abi_stable`.
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Mimics `abi_stable::std_types::RBox<T>`.
+#[repr(transparent)]
+pub struct RBox<T>(*mut T);
+
+impl<T> RBox<T> {
+    fn new(value: T) -> Self {
+        RBox(Box::into_raw(Box::new(value)))
+    }
+
+    fn into_raw(this: Self) -> *mut T {
+        let ptr = this.0;
+        std::mem::forget(this);
+        ptr
+    }
+
+    unsafe fn from_raw(ptr: *mut T) -> Self {
+        RBox(ptr)
+    }
+}
+
+impl<T> Drop for RBox<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = Box::from_raw(self.0);
+        }
+    }
+}
+
+/// Mimics `abi_stable::std_types::RString`, without type arguments.
+#[repr(C)]
+pub struct RString {
+    buffer: RBox<Counted>,
+}
+
+impl RString {
+    fn into_raw(this: Self) -> *mut RString {
+        Box::into_raw(Box::new(this))
+    }
+
+    unsafe fn from_raw(ptr: *mut RString) -> Self {
+        *Box::from_raw(ptr)
+    }
+}
+
+#[derive(Destruct)]
+#[repr(C)]
+pub struct Stable {
+    // Wrappers held by value drop themselves
+    owned: RBox<Counted>,
+    #[reclaim = "RBox<Counted>"]
+    boxed: *mut Counted,
+    #[ptr(box = "RBox")]
+    pointee: *mut Counted,
+    #[nullable]
+    #[reclaim = "RString"]
+    string: *mut RString,
+}
+
+#[test]
+fn test_abi_stable_wrappers() {
+    drop(Stable {
+        owned: RBox::new(Counted),
+        boxed: RBox::into_raw(RBox::new(Counted)),
+        pointee: RBox::into_raw(RBox::new(Counted)),
+        string: RString::into_raw(RString {
+            buffer: RBox::new(Counted),
+        }),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 4);
+}
@@ -0,0 +1,49 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Aliased {
+    data: *mut Counted,
+    #[alias_of = "data"]
+    current: *const Counted,
+}
+
+#[test]
+fn test_alias_of() {
+    let data = Box::into_raw(Box::new(Counted));
+    drop(Aliased {
+        data,
+        current: data,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "`current` is not an alias of `data`")]
+fn test_alias_of_mismatch() {
+    // Both are leaked, the assertion fires first
+    let data = Box::leak(Box::new(0u8)) as *mut u8;
+    let current = Box::leak(Box::new(0u8)) as *const u8;
+
+    #[derive(Destruct)]
+    struct Bytes {
+        #[alias_of = "data"]
+        current: *const u8,
+        #[no_drop]
+        data: *mut u8,
+    }
+    drop(Bytes { current, data });
+}
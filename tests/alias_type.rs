@@ -0,0 +1,45 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::extern_c_destructor;
+use std::ffi::CString;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// A generic list owning its items through a raw pointer.
+pub struct List<T> {
+    items: *mut T,
+    len: usize,
+}
+
+impl<T> List<T> {
+    fn new(items: Vec<T>) -> Self {
+        let len = items.len();
+        List {
+            items: Box::into_raw(items.into_boxed_slice()) as *mut T,
+            len,
+        }
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(self.len, Ordering::SeqCst);
+        unsafe {
+            let _ = Vec::from_raw_parts(self.items, self.len, self.len);
+        }
+    }
+}
+
+pub type StringList = List<CString>;
+
+extern_c_destructor!(StringList);
+
+#[test]
+fn test_generic_alias() {
+    let list = StringList::new(vec![CString::new("a").unwrap(), CString::new("b").unwrap()]);
+    unsafe {
+        destruct_string_list(Box::into_raw(Box::new(list)));
+    }
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,325 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_destructor, Destruct};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn into_array<T>(items: Vec<T>) -> *mut T {
+    Box::into_raw(items.into_boxed_slice()) as *mut T
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+enum Size {
+    Small = 2,
+    Large = 4,
+}
+
+impl Size {
+    fn count(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Destruct)]
+struct Sized {
+    #[array(len = "self.size.count()")]
+    items: *mut Counted,
+    size: Size,
+}
+
+#[test]
+fn test_array_len_expression() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    drop(Sized {
+        items: into_array((0..4).map(|_| Counted).collect()),
+        size: Size::Large,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 4);
+}
+
+#[derive(Destruct)]
+#[destruct(report)]
+pub struct Report {
+    #[array(len = "len")]
+    items: *mut u8,
+    len: u32,
+    #[nullable]
+    single: *mut u8,
+    #[no_drop]
+    external: *mut u8,
+}
+
+extern_c_destructor!(Report, report(out = "freed_count"));
+
+#[test]
+fn test_report() {
+    let report = Report {
+        items: into_array(vec![1, 2, 3]),
+        len: 3,
+        single: std::ptr::null_mut(),
+        external: std::ptr::null_mut(),
+    };
+    assert_eq!(report.destruct_count(), 3);
+
+    let mut freed_count = 0;
+    unsafe {
+        destruct_report(Box::into_raw(Box::new(report)), &mut freed_count);
+    }
+    assert_eq!(freed_count, 4);
+
+    unsafe {
+        destruct_report(std::ptr::null_mut(), &mut freed_count);
+    }
+    assert_eq!(freed_count, 0);
+
+    let report = Report {
+        items: into_array(vec![]),
+        len: 0,
+        single: Box::into_raw(Box::new(4)),
+        external: std::ptr::null_mut(),
+    };
+    unsafe {
+        destruct_report(Box::into_raw(Box::new(report)), std::ptr::null_mut());
+    }
+}
+
+static CHILD_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct ChildData;
+
+impl Drop for ChildData {
+    fn drop(&mut self) {
+        CHILD_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Child {
+    name: *mut std::ffi::c_char,
+    #[nullable]
+    data: *mut ChildData,
+}
+
+#[derive(Destruct)]
+struct Parent {
+    #[array(len = "n", require_drop)]
+    children: *mut Child,
+    n: usize,
+}
+
+#[test]
+fn test_array_of_structs() {
+    // The names inside the elements are freed without leaking, checked by LeakSanitizer in CI
+    let child = |data: bool| Child {
+        name: std::ffi::CString::new("child").unwrap().into_raw(),
+        data: if data {
+            Box::into_raw(Box::new(ChildData))
+        } else {
+            std::ptr::null_mut()
+        },
+    };
+    drop(Parent {
+        children: into_array(vec![child(true), child(false), child(true)]),
+        n: 3,
+    });
+    assert_eq!(CHILD_DROPPED.load(Ordering::SeqCst), 2);
+}
+
+static ELEMENTS_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Element;
+
+impl Drop for Element {
+    fn drop(&mut self) {
+        ELEMENTS_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+pub struct Entry {
+    name: *mut std::ffi::c_char,
+    element: *mut Element,
+}
+
+extern_c_destructor!(Entry, array);
+
+#[test]
+fn test_extern_array_of_structures() {
+    let entries = (0..3)
+        .map(|i| Entry {
+            name: std::ffi::CString::new(format!("entry {}", i))
+                .unwrap()
+                .into_raw(),
+            element: Box::into_raw(Box::new(Element)),
+        })
+        .collect::<Vec<_>>()
+        .into_boxed_slice();
+    let n = entries.len();
+    unsafe {
+        destruct_entry_array(Box::into_raw(entries) as *mut Entry, n);
+        destruct_entry_array(std::ptr::null_mut(), 0);
+    }
+    assert_eq!(ELEMENTS_DROPPED.load(Ordering::SeqCst), 3);
+}
+
+static BUFFER_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct BufferElement;
+
+impl Drop for BufferElement {
+    fn drop(&mut self) {
+        BUFFER_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Buffer {
+    #[array(len = "count")]
+    elements: *mut BufferElement,
+    count: usize,
+    #[array(len = "chars_len")]
+    chars: *mut std::ffi::c_char,
+    chars_len: u32,
+}
+
+#[test]
+fn test_array_edge_cases() {
+    let before = BUFFER_DROPPED.load(Ordering::SeqCst);
+    drop(Buffer {
+        elements: into_array(vec![BufferElement, BufferElement]),
+        count: 2,
+        // Not NUL-terminated, reclaimed as a `Vec<c_char>`
+        chars: into_array(vec![b'a' as std::ffi::c_char, b'b' as std::ffi::c_char]),
+        chars_len: 2,
+    });
+    assert_eq!(BUFFER_DROPPED.load(Ordering::SeqCst), before + 2);
+
+    // Zero-length arrays with a dangling pointer
+    drop(Buffer {
+        elements: into_array(vec![]),
+        count: 0,
+        chars: into_array(vec![]),
+        chars_len: 0,
+    });
+    assert_eq!(BUFFER_DROPPED.load(Ordering::SeqCst), before + 2);
+
+    // Null with a length of zero, as returned by C for empty arrays
+    drop(Buffer {
+        elements: std::ptr::null_mut(),
+        count: 0,
+        chars: std::ptr::null_mut(),
+        chars_len: 0,
+    });
+    assert_eq!(BUFFER_DROPPED.load(Ordering::SeqCst), before + 2);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "`elements` is null with a length of 1")]
+fn test_array_null_with_len() {
+    drop(Buffer {
+        elements: std::ptr::null_mut(),
+        count: 1,
+        chars: std::ptr::null_mut(),
+        chars_len: 0,
+    });
+}
+
+static CONSTANT_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Fixed;
+
+impl Drop for Fixed {
+    fn drop(&mut self) {
+        CONSTANT_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(report)]
+struct Buffers {
+    #[array(len = 256)]
+    name: *mut std::ffi::c_char,
+    #[array(len = 3)]
+    fixed: *mut Fixed,
+    #[array(len = "count")]
+    counted: *mut Fixed,
+    count: usize,
+}
+
+#[test]
+fn test_array_len_literal() {
+    let buffers = Buffers {
+        name: into_array(vec![0; 256]),
+        fixed: into_array((0..3).map(|_| Fixed).collect()),
+        counted: into_array((0..2).map(|_| Fixed).collect()),
+        count: 2,
+    };
+    assert_eq!(buffers.destruct_count(), 256 + 3 + 2);
+    drop(buffers);
+    assert_eq!(CONSTANT_DROPPED.load(Ordering::SeqCst), 5);
+}
+
+static VEC_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Item(u64);
+
+impl Drop for Item {
+    fn drop(&mut self) {
+        VEC_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(report)]
+struct LeakedVec {
+    #[vec(len = "len", cap = "cap")]
+    data: *mut Item,
+    len: usize,
+    cap: usize,
+    #[nullable]
+    #[vec(len = "bytes_len", cap = 64)]
+    bytes: *mut u8,
+    bytes_len: u32,
+}
+
+#[test]
+fn test_vec_len_cap() {
+    let mut items = Vec::with_capacity(8);
+    items.extend((0..3).map(Item));
+    let mut items = std::mem::ManuallyDrop::new(items);
+    let mut bytes = Vec::with_capacity(64);
+    bytes.extend_from_slice(b"bytes");
+    let mut bytes = std::mem::ManuallyDrop::new(bytes);
+    let buffer = LeakedVec {
+        data: items.as_mut_ptr(),
+        len: items.len(),
+        cap: items.capacity(),
+        bytes: bytes.as_mut_ptr(),
+        bytes_len: bytes.len() as u32,
+    };
+    assert_eq!(buffer.len, 3);
+    assert_eq!(buffer.cap, 8);
+    assert_eq!(buffer.destruct_count(), 3 + 5);
+    drop(buffer);
+    assert_eq!(VEC_DROPPED.load(Ordering::SeqCst), 3);
+
+    drop(LeakedVec {
+        data: std::mem::ManuallyDrop::new(Vec::<Item>::with_capacity(4)).as_mut_ptr(),
+        len: 0,
+        cap: 4,
+        bytes: std::ptr::null_mut(),
+        bytes_len: 0,
+    });
+    assert_eq!(VEC_DROPPED.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,48 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{atexit_destructor, Destruct};
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Global {
+    a: *mut Counted,
+}
+
+static GLOBAL_STRUCT: AtomicPtr<Global> = AtomicPtr::new(std::ptr::null_mut());
+
+atexit_destructor!(GLOBAL_STRUCT);
+
+#[test]
+fn test_atexit_destructor() {
+    let global = Box::new(Global {
+        a: Box::into_raw(Box::new(Counted)),
+    });
+    GLOBAL_STRUCT.store(Box::into_raw(global), Ordering::Release);
+
+    let destructor: extern "C" fn() = atexit_global_struct;
+    destructor();
+    assert!(GLOBAL_STRUCT.load(Ordering::Acquire).is_null());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+
+    // Already freed
+    destructor();
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "libc")]
+#[test]
+fn test_register_atexit() {
+    // Registered once, the destructor runs at exit with the global null or set
+    assert!(register_atexit_global_struct());
+    assert!(register_atexit_global_struct());
+}
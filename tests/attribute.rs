@@ -0,0 +1,52 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::ffi_destruct;
+use std::ffi::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[ffi_destruct]
+#[derive(Debug)]
+struct Plain {
+    a: *mut Counted,
+    #[nullable]
+    b: *mut Counted,
+}
+
+#[ffi_destruct(extern_c, repr_c)]
+pub struct Exported {
+    name: *mut c_char,
+    #[no_drop]
+    borrowed: *const Counted,
+    count: u32,
+}
+
+#[test]
+fn test_attribute() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    drop(Plain {
+        a: Box::into_raw(Box::new(Counted)),
+        b: std::ptr::null_mut(),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+
+    let borrowed = Counted;
+    let exported = Exported {
+        name: CString::new("exported").unwrap().into_raw(),
+        borrowed: &borrowed,
+        count: 1,
+    };
+    unsafe {
+        destruct_exported(Box::into_raw(Box::new(exported)));
+    }
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+}
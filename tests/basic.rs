@@ -120,3 +120,164 @@ fn test() {
         destruct_test_f(ptr_f);
     }
 }
+
+#[derive(Destruct)]
+pub struct Buffer {
+    #[no_drop]
+    data: *mut f32,
+    name: *mut c_char,
+}
+
+extern_c_destructor!(Buffer, array(field = "data", elem = "f32", len_arg));
+
+#[test]
+fn test_array_len_arg() {
+    let data = vec![1.0f32, 2.0, 3.0].into_boxed_slice();
+    let len = data.len();
+    let buffer = Buffer {
+        data: Box::into_raw(data) as *mut f32,
+        name: CString::new("buffer").unwrap().into_raw(),
+    };
+    unsafe {
+        destruct_buffer(Box::into_raw(Box::new(buffer)), len);
+        destruct_buffer(std::ptr::null_mut(), 0);
+    }
+}
+
+extern_c_destructor!(TestF, name = "mylib_test_f_free");
+extern_c_destructor!(TestF, name = "TestF_destroy");
+
+#[test]
+fn test_custom_names() {
+    let f = || {
+        Box::into_raw(Box::new(TestF {
+            a: Box::into_raw(Box::new(TestE())),
+            b: Box::into_raw(Box::new(TestE())),
+        }))
+    };
+    unsafe {
+        destruct_test_f(f());
+        mylib_test_f_free(f());
+        TestF_destroy(f());
+    }
+}
+
+// The exported symbol, resolved by the linker rather than the Rust path
+extern "C" {
+    #[link_name = "mylib_test_f_free"]
+    fn linked_test_f_free(ptr: *mut std::ffi::c_void);
+}
+
+#[test]
+fn test_custom_name_symbol() {
+    let f = Box::into_raw(Box::new(TestF {
+        a: Box::into_raw(Box::new(TestE())),
+        b: Box::into_raw(Box::new(TestE())),
+    }));
+    unsafe {
+        linked_test_f_free(f as *mut std::ffi::c_void);
+    }
+}
+
+#[derive(Destruct)]
+pub struct TestUnwind {
+    a: *mut TestE,
+}
+
+extern_c_destructor!(TestUnwind, abi = "C-unwind");
+extern_c_destructor!(TestF, name = "destruct_test_f_system", abi = "system");
+
+#[test]
+fn test_abi() {
+    let f: unsafe extern "C-unwind" fn(*mut TestUnwind) = destruct_test_unwind;
+    unsafe {
+        f(Box::into_raw(Box::new(TestUnwind {
+            a: Box::into_raw(Box::new(TestE())),
+        })));
+    }
+    let f: unsafe extern "system" fn(*mut TestF) = destruct_test_f_system;
+    unsafe {
+        f(Box::into_raw(Box::new(TestF {
+            a: Box::into_raw(Box::new(TestE())),
+            b: std::ptr::null_mut(),
+        })));
+    }
+}
+
+/// Not a C string, despite containing `c_char` in its name.
+#[allow(non_camel_case_types)]
+struct my_c_char_wrapper(u8);
+
+#[derive(Destruct)]
+struct TestG {
+    a: *mut my_c_char_wrapper,
+}
+
+#[test]
+fn test_c_char_lookalike() {
+    // Freed with `Box`, a `CString` would read past the single byte
+    drop(TestG {
+        a: Box::into_raw(Box::new(my_c_char_wrapper(b'a'))),
+    });
+}
+
+extern_c_destructor!(TestG, doc_hidden);
+
+#[test]
+fn test_doc_hidden() {
+    unsafe {
+        destruct_test_g(Box::into_raw(Box::new(TestG {
+            a: Box::into_raw(Box::new(my_c_char_wrapper(b'a'))),
+        })));
+    }
+}
+
+#[derive(Destruct)]
+pub struct TestVersioned {
+    a: *mut my_c_char_wrapper,
+}
+
+extern_c_destructor!(TestVersioned, abi_version = 3);
+
+#[test]
+fn test_abi_version() {
+    let data = Box::into_raw(Box::new(my_c_char_wrapper(b'a')));
+    let ptr = Box::into_raw(Box::new(TestVersioned { a: data }));
+    unsafe {
+        // Mismatch leaves the structure intact
+        destruct_test_versioned(ptr, 2);
+        assert_eq!((*(*ptr).a).0, b'a');
+        destruct_test_versioned(ptr, 3);
+        destruct_test_versioned(std::ptr::null_mut(), 3);
+    }
+}
+
+#[derive(Destruct)]
+struct TestH {
+    #[cfg_not_miri_skip]
+    a: *mut TestE,
+}
+
+#[test]
+fn test_cfg_not_miri_skip() {
+    // Freed as usual outside of Miri
+    drop(TestH {
+        a: Box::into_raw(Box::new(TestE())),
+    });
+}
+
+#[derive(Destruct)]
+struct TestI {
+    a: *mut c_char,
+    // Still dropped by Rust, like any other field
+    #[no_drop]
+    normal: String,
+}
+
+#[test]
+fn test_no_drop_non_pointer() {
+    drop(TestI {
+        a: CString::new("a").unwrap().into_raw(),
+        normal: "normal".to_string(),
+    });
+}
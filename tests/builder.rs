@@ -0,0 +1,96 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(builder)]
+pub struct Structure {
+    name: *mut c_char,
+    data: *mut Counted,
+    #[nullable]
+    other: *mut Counted,
+    owned: Box<Counted>,
+}
+
+#[derive(Destruct)]
+#[destruct(builder)]
+struct Private {
+    data: *mut Counted,
+}
+
+// Fields named like the methods of the builder
+#[derive(Destruct)]
+#[destruct(builder)]
+struct Methods {
+    new: *mut Counted,
+    build: *mut Counted,
+    r#type: u32,
+}
+
+fn allocate(fail: bool) -> Option<*mut Counted> {
+    (!fail).then(|| Box::into_raw(Box::new(Counted)))
+}
+
+fn construct(fail: bool) -> Result<Structure, StructureBuilder> {
+    let mut builder = StructureBuilder::new();
+    builder
+        .with_name(CString::new("name").unwrap().into_raw())
+        .with_data(Box::into_raw(Box::new(Counted)));
+    match allocate(fail) {
+        Some(other) => builder.with_other(other),
+        None => return Err(builder),
+    };
+    builder.with_owned(Box::new(Counted));
+    builder.build()
+}
+
+#[test]
+fn test_builder() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let structure = construct(false).ok().unwrap();
+    drop(structure);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 3);
+
+    // Failed midway, only `name` and `data` are freed
+    let builder = construct(true).err().unwrap();
+    let builder = builder.build().err().unwrap();
+    drop(builder);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 4);
+
+    let mut builder = PrivateBuilder::new();
+    builder.with_data(Box::into_raw(Box::new(Counted)));
+    drop(builder.build().ok().unwrap());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 5);
+}
+
+#[test]
+fn test_builder_method_names() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let mut builder = MethodsBuilder::new();
+    builder
+        .with_new(Box::into_raw(Box::new(Counted)))
+        .with_build(Box::into_raw(Box::new(Counted)))
+        .with_type(1);
+    drop(builder.build().ok().unwrap());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+}
+
+#[test]
+#[should_panic(expected = "`name` is already set")]
+fn test_builder_set_twice() {
+    let mut builder = StructureBuilder::default();
+    builder.with_name(CString::new("name").unwrap().into_raw());
+    builder.with_name(std::ptr::null_mut());
+}
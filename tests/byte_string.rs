@@ -0,0 +1,44 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::c_char;
+
+#[derive(Destruct)]
+#[destruct(report)]
+struct Message {
+    #[byte_string(len = "len")]
+    text: *mut c_char,
+    len: usize,
+    #[nullable]
+    #[byte_string(len = "payload_len")]
+    payload: *const u8,
+    payload_len: u32,
+}
+
+fn bytes(data: &[u8]) -> (*mut u8, usize) {
+    let boxed = data.to_vec().into_boxed_slice();
+    let len = boxed.len();
+    (Box::into_raw(boxed) as *mut u8, len)
+}
+
+#[test]
+fn test_byte_string() {
+    // Neither is NUL-terminated
+    let (text, len) = bytes(b"hello");
+    let (payload, payload_len) = bytes(&[0xff; 3]);
+    let message = Message {
+        text: text as *mut c_char,
+        len,
+        payload,
+        payload_len: payload_len as u32,
+    };
+    assert_eq!(message.destruct_count(), 2);
+    drop(message);
+
+    drop(Message {
+        text: bytes(b"").0 as *mut c_char,
+        len: 0,
+        payload: std::ptr::null(),
+        payload_len: 0,
+    });
+}
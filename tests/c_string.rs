@@ -0,0 +1,89 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::CString;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Destruct)]
+struct Bindings {
+    #[c_string]
+    signed: *mut i8,
+    #[c_string]
+    unsigned: *const u8,
+    #[c_string]
+    #[nullable]
+    optional: *mut u8,
+}
+
+#[test]
+fn test_c_string() {
+    drop(Bindings {
+        signed: CString::new("signed").unwrap().into_raw().cast(),
+        unsigned: CString::new("unsigned")
+            .unwrap()
+            .into_raw()
+            .cast_const()
+            .cast(),
+        optional: CString::new("optional").unwrap().into_raw().cast(),
+    });
+    drop(Bindings {
+        signed: CString::new("signed").unwrap().into_raw().cast(),
+        unsigned: CString::new("unsigned")
+            .unwrap()
+            .into_raw()
+            .cast_const()
+            .cast(),
+        optional: std::ptr::null_mut(),
+    });
+}
+
+static DECOYS_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// Named like `c_char`, but not a C string.
+#[allow(non_camel_case_types)]
+struct c_char_wrapper(u64);
+
+impl Drop for c_char_wrapper {
+    fn drop(&mut self) {
+        DECOYS_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+mod c_char_utils {
+    pub struct Thing(pub u64);
+
+    impl Drop for Thing {
+        fn drop(&mut self) {
+            super::DECOYS_DROPPED.fetch_add(1, super::Ordering::SeqCst);
+        }
+    }
+}
+
+/// A module named `c_char`, only the last segment of the path names the pointee.
+mod c_char {
+    pub struct Handle(pub u64);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            super::DECOYS_DROPPED.fetch_add(1, super::Ordering::SeqCst);
+        }
+    }
+}
+
+#[derive(Destruct)]
+struct Decoys {
+    wrapper: *mut c_char_wrapper,
+    thing: *mut c_char_utils::Thing,
+    handle: *mut c_char::Handle,
+}
+
+#[test]
+fn test_c_char_decoys() {
+    // Freed as a `Box` of their own type, only a last segment of exactly `c_char` is a C string
+    drop(Decoys {
+        wrapper: Box::into_raw(Box::new(c_char_wrapper(1))),
+        thing: Box::into_raw(Box::new(c_char_utils::Thing(2))),
+        handle: Box::into_raw(Box::new(c_char::Handle(3))),
+    });
+    assert_eq!(DECOYS_DROPPED.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,49 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted(u32);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// The host keeps the objects marked with `1`.
+extern "C" fn should_free(ptr: *mut c_void) -> bool {
+    assert!(!ptr.is_null());
+    unsafe { (*(ptr as *mut Counted)).0 != 1 }
+}
+
+#[derive(Destruct)]
+struct Hosted {
+    #[free_if_callback = "should_free"]
+    a: *mut Counted,
+    #[nullable]
+    #[free_if_callback = "should_free"]
+    b: *mut Counted,
+    should_free: Option<extern "C" fn(*mut c_void) -> bool>,
+}
+
+#[test]
+fn test_free_if_callback() {
+    let kept = Box::into_raw(Box::new(Counted(1)));
+    drop(Hosted {
+        a: Box::into_raw(Box::new(Counted(2))),
+        b: kept,
+        should_free: Some(should_free),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+
+    drop(Hosted {
+        a: kept,
+        b: std::ptr::null_mut(),
+        should_free: None,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+}
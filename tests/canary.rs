@@ -0,0 +1,46 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+const MAGIC: u32 = 0xDEADBEEF;
+
+#[derive(Destruct)]
+#[destruct(canary = "magic", value = "0xDEADBEEF")]
+#[repr(C)]
+struct Guarded {
+    magic: u32,
+    data: *mut Counted,
+}
+
+#[test]
+fn test_canary() {
+    drop(Guarded {
+        magic: MAGIC,
+        data: Box::into_raw(Box::new(Counted)),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "Canary `magic` of `Guarded` does not match")
+)]
+fn test_canary_mismatch() {
+    // Leaked, as the destructor refuses to free it
+    drop(Guarded {
+        magic: 0,
+        data: Box::into_raw(Box::new(Counted)),
+    });
+}
@@ -0,0 +1,38 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_destructor, Destruct};
+
+#[repr(C)]
+#[derive(Destruct)]
+pub struct MyStruct {
+    value: *mut u32,
+}
+
+#[repr(C)]
+#[derive(Destruct)]
+#[allow(non_camel_case_types)]
+pub struct my_handle {
+    value: *mut u32,
+}
+
+extern_c_destructor!(MyStruct, case = "preserve");
+extern_c_destructor!(my_handle, case = "pascal");
+
+extern "C" {
+    #[link_name = "destruct_MyStruct"]
+    fn linked_destruct_my_struct(ptr: *mut MyStruct);
+    #[link_name = "destruct_MyHandle"]
+    fn linked_destruct_my_handle(ptr: *mut my_handle);
+}
+
+#[test]
+fn test_case() {
+    unsafe {
+        linked_destruct_my_struct(Box::into_raw(Box::new(MyStruct {
+            value: Box::into_raw(Box::new(1)),
+        })));
+        linked_destruct_my_handle(Box::into_raw(Box::new(my_handle {
+            value: Box::into_raw(Box::new(2)),
+        })));
+    }
+}
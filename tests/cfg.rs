@@ -0,0 +1,101 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(cfg = "all()")]
+struct Enabled {
+    a: *mut Counted,
+}
+
+#[derive(Destruct)]
+#[destruct(cfg = "any()")]
+struct Disabled {
+    a: *mut Counted,
+}
+
+#[derive(Destruct)]
+#[destruct(cfg = "unix")]
+struct UnixOnly {
+    a: *mut Counted,
+}
+
+#[test]
+fn test_cfg() {
+    assert!(std::mem::needs_drop::<Enabled>());
+    assert!(!std::mem::needs_drop::<Disabled>());
+    assert_eq!(std::mem::needs_drop::<UnixOnly>(), cfg!(unix));
+
+    let before = DROPPED.load(Ordering::SeqCst);
+    drop(Enabled {
+        a: Box::into_raw(Box::new(Counted)),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+
+    // Without `Drop`, the pointee is left to be freed manually.
+    let ptr = Box::into_raw(Box::new(Counted));
+    let _ = Disabled { a: ptr };
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+    unsafe {
+        let _ = Box::from_raw(ptr);
+    }
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+}
+
+ffi_destruct::extern_c_destructor!(Enabled, cfg = "all()");
+ffi_destruct::extern_c_destructor!(Disabled, cfg = "any()");
+
+/// Does not conflict with the compiled-out destructor.
+unsafe extern "C" fn destruct_disabled(ptr: *mut Disabled) {
+    let _ = Box::from_raw((*ptr).a);
+    let _ = Box::from_raw(ptr);
+}
+
+#[test]
+fn test_extern_cfg() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    unsafe {
+        destruct_enabled(Box::into_raw(Box::new(Enabled {
+            a: Box::into_raw(Box::new(Counted)),
+        })));
+        destruct_disabled(Box::into_raw(Box::new(Disabled {
+            a: Box::into_raw(Box::new(Counted)),
+        })));
+    }
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+}
+
+#[derive(Destruct, Default, Clone)]
+struct Gated {
+    #[nullable]
+    a: *mut Counted,
+}
+
+ffi_destruct::extern_c_constructor!(Gated, cfg = "all()");
+ffi_destruct::extern_c_clone!(Gated, cfg = "any()");
+
+/// Does not conflict with the compiled-out clone.
+unsafe extern "C" fn clone_gated(ptr: *const Gated) -> *mut Gated {
+    std::ptr::null_mut()
+}
+
+#[test]
+fn test_extern_cfg_constructor_clone() {
+    unsafe {
+        let ptr = new_gated();
+        assert!((*ptr).a.is_null());
+        assert!(clone_gated(ptr).is_null());
+        drop(Box::from_raw(ptr));
+    }
+}
@@ -0,0 +1,47 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_destructor, Destruct};
+use std::ffi::{c_char, CString};
+
+#[derive(Destruct)]
+pub struct Structure {
+    name: *mut c_char,
+}
+
+extern_c_destructor!(Structure);
+extern_c_destructor!(Structure, checked);
+extern_c_destructor!(Structure, array, checked);
+extern_c_destructor!(
+    Structure,
+    name = "destruct_structure_v2",
+    abi_version = 2,
+    checked
+);
+
+fn structure() -> Structure {
+    Structure {
+        name: CString::new("name").unwrap().into_raw(),
+    }
+}
+
+#[test]
+fn test_checked() {
+    unsafe {
+        assert_eq!(
+            destruct_structure_checked(Box::into_raw(Box::new(structure()))),
+            0
+        );
+        assert_eq!(destruct_structure_checked(std::ptr::null_mut()), -1);
+
+        let array = Box::into_raw(vec![structure(), structure()].into_boxed_slice());
+        assert_eq!(destruct_structure_array_checked(array.cast(), 2), 0);
+
+        let skipped = Box::into_raw(Box::new(structure()));
+        assert_eq!(destruct_structure_v2(skipped, 1), -2);
+        assert_eq!(destruct_structure_v2(skipped, 2), 0);
+        assert_eq!(destruct_structure_v2(std::ptr::null_mut(), 2), -1);
+
+        // The unchecked form returns nothing
+        let () = destruct_structure(Box::into_raw(Box::new(structure())));
+    }
+}
@@ -0,0 +1,58 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_clone, extern_c_destructor, Destruct};
+use std::ffi::{c_char, CStr, CString};
+
+#[derive(Destruct)]
+pub struct Named {
+    name: *mut c_char,
+    #[nullable]
+    next: *mut Named,
+}
+
+impl Clone for Named {
+    fn clone(&self) -> Self {
+        let name = unsafe { CStr::from_ptr(self.name) }.to_owned();
+        let next = match unsafe { self.next.as_ref() } {
+            Some(next) => Box::into_raw(Box::new(next.clone())),
+            None => std::ptr::null_mut(),
+        };
+        Named {
+            name: name.into_raw(),
+            next,
+        }
+    }
+}
+
+extern_c_clone!(Named);
+extern_c_clone!(Named, name = "named_copy");
+extern_c_destructor!(Named);
+
+fn named(name: &str, next: *mut Named) -> *mut Named {
+    Box::into_raw(Box::new(Named {
+        name: CString::new(name).unwrap().into_raw(),
+        next,
+    }))
+}
+
+#[test]
+fn test_extern_c_clone() {
+    unsafe {
+        let original = named("a", named("b", std::ptr::null_mut()));
+        let clone = clone_named(original);
+        assert_ne!(clone, original);
+        assert_ne!((*clone).name, (*original).name);
+        assert_ne!((*clone).next, (*original).next);
+
+        // Both are freed independently
+        destruct_named(original);
+        assert_eq!(CStr::from_ptr((*clone).name).to_str(), Ok("a"));
+        assert_eq!(CStr::from_ptr((*(*clone).next).name).to_str(), Ok("b"));
+
+        let copy = named_copy(clone);
+        destruct_named(clone);
+        destruct_named(copy);
+
+        assert!(clone_named(std::ptr::null()).is_null());
+    }
+}
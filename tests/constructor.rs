@@ -0,0 +1,54 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_constructor, extern_c_destructor, Destruct};
+use std::ffi::{c_char, CString};
+
+#[derive(Destruct)]
+pub struct Settings {
+    #[nullable]
+    name: *mut c_char,
+    level: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            name: CString::new("default").unwrap().into_raw(),
+            level: 3,
+        }
+    }
+}
+
+extern_c_constructor!(Settings);
+extern_c_constructor!(Settings, default, name = "settings_create");
+extern_c_destructor!(Settings);
+
+#[derive(Destruct)]
+pub struct Zeroed {
+    #[nullable]
+    name: *mut c_char,
+    len: usize,
+}
+
+extern_c_constructor!(Zeroed, zeroed);
+extern_c_destructor!(Zeroed);
+
+#[test]
+fn test_constructor() {
+    unsafe {
+        let settings = new_settings();
+        assert_eq!((*settings).level, 3);
+        destruct_settings(settings);
+
+        let settings = settings_create();
+        assert!(!(*settings).name.is_null());
+        destruct_settings(settings);
+
+        let zeroed = new_zeroed();
+        assert!((*zeroed).name.is_null());
+        assert_eq!((*zeroed).len, 0);
+        // Filled in by C after allocation
+        (*zeroed).name = CString::new("filled").unwrap().into_raw();
+        destruct_zeroed(zeroed);
+    }
+}
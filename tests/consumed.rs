@@ -0,0 +1,49 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(consumed_flag = "consumed")]
+struct Wrapper {
+    data: *mut Counted,
+    consumed: bool,
+}
+
+/// Stands in for a C function taking the ownership of the pointer.
+unsafe fn take_ownership(data: *mut Counted) {
+    let _ = Box::from_raw(data);
+}
+
+#[test]
+fn test_consumed_flag() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    drop(Wrapper {
+        data: Box::into_raw(Box::new(Counted)),
+        consumed: false,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+
+    // Transferred, then dropped: only the new owner frees the pointer
+    let mut wrapper = Wrapper {
+        data: Box::into_raw(Box::new(Counted)),
+        consumed: false,
+    };
+    wrapper.into_ffi();
+    assert!(wrapper.consumed);
+    unsafe {
+        take_ownership(wrapper.data);
+    }
+    drop(wrapper);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+}
@@ -0,0 +1,84 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread::ThreadId;
+use std::time::Duration;
+
+/// A handle reporting the thread closing it.
+struct Handle(Mutex<Sender<ThreadId>>);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        let _ = self.0.lock().unwrap().send(std::thread::current().id());
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(defer)]
+struct Connection {
+    #[drop_if = "self.open"]
+    handle: *mut Handle,
+    #[nullable]
+    name: *mut std::ffi::c_char,
+    open: bool,
+    label: String,
+}
+
+#[test]
+fn test_defer() {
+    let (sender, receiver) = channel();
+    let connection = |open| Connection {
+        handle: Box::into_raw(Box::new(Handle(Mutex::new(sender.clone())))),
+        name: std::ffi::CString::new("connection").unwrap().into_raw(),
+        open,
+        label: "label".to_string(),
+    };
+
+    drop(connection(true));
+    drop(connection(true));
+    let first = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    let second = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_ne!(first, std::thread::current().id());
+    assert_eq!(first, second);
+
+    // Not freed, so the handle is leaked and never reports
+    drop(connection(false));
+    assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+/// A handle printing when closed.
+struct Printed;
+
+impl Drop for Printed {
+    fn drop(&mut self) {
+        println!("freed");
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(defer)]
+struct Locked {
+    handle: Mutex<*mut Printed>,
+}
+
+#[test]
+fn test_defer_exit() {
+    // The child process exits right after the drop, without returning from the test
+    if std::env::var_os("DEFER_EXIT").is_some() {
+        drop(Locked {
+            handle: Mutex::new(Box::into_raw(Box::new(Printed))),
+        });
+        Locked::flush_deferred();
+        std::process::exit(0);
+    }
+
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "test_defer_exit", "--nocapture"])
+        .env("DEFER_EXIT", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("freed"));
+}
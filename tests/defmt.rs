@@ -0,0 +1,50 @@
+#![cfg(feature = "defmt")]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Discards the frames, the host has no probe to decode them.
+#[defmt::global_logger]
+struct Discard;
+
+unsafe impl defmt::Logger for Discard {
+    fn acquire() {}
+
+    unsafe fn flush() {}
+
+    unsafe fn release() {}
+
+    unsafe fn write(_: &[u8]) {}
+}
+
+defmt::timestamp!("{=u32}", 0);
+
+#[derive(Destruct)]
+struct Traced {
+    first: *mut Counted,
+    #[nullable]
+    missing: *mut Counted,
+    second: *mut Counted,
+}
+
+#[test]
+fn test_defmt() {
+    // The messages are only encoded with `DEFMT_LOG=trace`, so only check the fields are freed
+    drop(Traced {
+        first: Box::into_raw(Box::new(Counted)),
+        missing: std::ptr::null_mut(),
+        second: Box::into_raw(Box::new(Counted)),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,55 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{destructible, Destruct};
+use std::ffi::{c_char, CString};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+destructible!();
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(destructible)]
+pub struct Node {
+    value: *mut Counted,
+}
+
+#[derive(Destruct)]
+#[destruct(destructible)]
+pub struct Named<T> {
+    name: *mut c_char,
+    value: *mut T,
+}
+
+unsafe fn free_all<T: Destructible>(ptrs: &[*mut T]) {
+    for &ptr in ptrs {
+        T::free_raw(ptr);
+    }
+}
+
+#[test]
+fn test_free_all() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let node = || {
+        Box::into_raw(Box::new(Node {
+            value: Box::into_raw(Box::new(Counted)),
+        }))
+    };
+    unsafe { free_all(&[node(), std::ptr::null_mut(), node()]) };
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+
+    let named = Box::into_raw(Box::new(Named {
+        name: CString::new("named").unwrap().into_raw(),
+        value: Box::into_raw(Box::new(Counted)),
+    }));
+    unsafe { free_all(&[named]) };
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 3);
+}
@@ -0,0 +1,61 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, CString};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Arrays {
+    #[array(len = "argc")]
+    argv: *mut *mut c_char,
+    argc: usize,
+    #[array(len = "n")]
+    items: *mut *mut Counted,
+    n: u32,
+    // Without a length: a `Box<*mut Counted>`
+    single: *mut *mut Counted,
+    #[nullable]
+    constant: *const *const Counted,
+}
+
+fn into_array<T>(items: Vec<T>) -> *mut T {
+    Box::into_raw(items.into_boxed_slice()) as *mut T
+}
+
+#[test]
+fn test_double_pointer() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let strings = ["a", "b"].map(|s| CString::new(s).unwrap().into_raw());
+    let counted = || Box::into_raw(Box::new(Counted));
+    drop(Arrays {
+        argv: into_array(strings.to_vec()),
+        argc: 2,
+        // Null elements are skipped
+        items: into_array(vec![counted(), std::ptr::null_mut(), counted()]),
+        n: 3,
+        single: Box::into_raw(Box::new(counted())),
+        constant: Box::into_raw(Box::new(counted() as *const Counted)),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 4);
+
+    // A null inner pointer only frees the outer `Box`
+    drop(Arrays {
+        argv: std::ptr::null_mut(),
+        argc: 0,
+        items: into_array(vec![]),
+        n: 0,
+        single: Box::into_raw(Box::new(std::ptr::null_mut())),
+        constant: std::ptr::null(),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 4);
+}
@@ -0,0 +1,110 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+static GLOBAL_OWNS: AtomicBool = AtomicBool::new(true);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Global {
+    #[drop_if = "crate::GLOBAL_OWNS.load(Ordering::Relaxed)"]
+    a: *mut Counted,
+}
+
+#[derive(Destruct)]
+struct Local {
+    #[nullable]
+    #[drop_if = "self.owned"]
+    a: *mut Counted,
+    owned: bool,
+}
+
+#[test]
+fn test_drop_if() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    drop(Global {
+        a: Box::into_raw(Box::new(Counted)),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+
+    GLOBAL_OWNS.store(false, Ordering::Relaxed);
+    let ptr = Box::into_raw(Box::new(Counted));
+    drop(Global { a: ptr });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+    unsafe {
+        let _ = Box::from_raw(ptr);
+    }
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+
+    let ptr = Box::into_raw(Box::new(Counted));
+    drop(Local {
+        a: ptr,
+        owned: false,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+    drop(Local {
+        a: ptr,
+        owned: true,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 3);
+    drop(Local {
+        a: std::ptr::null_mut(),
+        owned: true,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 3);
+}
+
+static DATA_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Data;
+
+impl Drop for Data {
+    fn drop(&mut self) {
+        DATA_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Owned {
+    #[owned_if = "owns_data"]
+    data: *mut Data,
+    owns_data: bool,
+}
+
+#[test]
+fn test_owned_if() {
+    let before = DATA_DROPPED.load(Ordering::SeqCst);
+    drop(Owned {
+        data: Box::into_raw(Box::new(Data)),
+        owns_data: true,
+    });
+    assert_eq!(DATA_DROPPED.load(Ordering::SeqCst), before + 1);
+
+    // Borrowed instances leave the pointee to its owner
+    let mut borrowed = Data;
+    drop(Owned {
+        data: &mut borrowed,
+        owns_data: false,
+    });
+    assert_eq!(DATA_DROPPED.load(Ordering::SeqCst), before + 1);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "`data` is null while `owns_data` is set")]
+fn test_owned_if_null() {
+    drop(Owned {
+        data: std::ptr::null_mut(),
+        owns_data: true,
+    });
+}
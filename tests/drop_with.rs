@@ -0,0 +1,60 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static FREED: AtomicUsize = AtomicUsize::new(0);
+
+/// Stands in for a C allocator's free function, e.g. `libc::free`.
+unsafe extern "C" fn dummy_free(ptr: *mut c_void) {
+    FREED.fetch_add(1, Ordering::SeqCst);
+    let _ = Box::from_raw(ptr as *mut u64);
+}
+
+#[derive(Destruct)]
+struct Allocated {
+    #[drop_with = "dummy_free"]
+    data: *mut u64,
+    #[nullable]
+    #[drop_with = "crate::dummy_free"]
+    optional: *mut u64,
+}
+
+#[test]
+fn test_drop_with() {
+    let before = FREED.load(Ordering::SeqCst);
+    drop(Allocated {
+        data: Box::into_raw(Box::new(1)),
+        optional: Box::into_raw(Box::new(2)),
+    });
+    assert_eq!(FREED.load(Ordering::SeqCst), before + 2);
+
+    // The null check still wraps the custom free
+    drop(Allocated {
+        data: Box::into_raw(Box::new(1)),
+        optional: std::ptr::null_mut(),
+    });
+    assert_eq!(FREED.load(Ordering::SeqCst), before + 3);
+}
+
+static OPAQUE_FREED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe extern "C" fn opaque_free(ptr: *mut c_void) {
+    OPAQUE_FREED.fetch_add(1, Ordering::SeqCst);
+    let _ = Box::from_raw(ptr as *mut u64);
+}
+
+#[derive(Destruct)]
+struct Opaque {
+    #[drop_with = "opaque_free"]
+    handle: *mut c_void,
+}
+
+#[test]
+fn test_drop_with_c_void() {
+    drop(Opaque {
+        handle: Box::into_raw(Box::new(1u64)).cast(),
+    });
+    assert_eq!(OPAQUE_FREED.load(Ordering::SeqCst), 1);
+}
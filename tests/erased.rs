@@ -0,0 +1,47 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Payload(Vec<u32>);
+
+impl Drop for Payload {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(self.0.len(), Ordering::SeqCst);
+    }
+}
+
+unsafe fn drop_payload(ptr: *mut ()) {
+    let _ = Box::from_raw(ptr as *mut Payload);
+}
+
+#[derive(Destruct)]
+struct Erased {
+    #[nullable]
+    #[erased(drop_fn = "drop_fn")]
+    payload: *mut (),
+    drop_fn: unsafe fn(*mut ()),
+}
+
+fn erase<T>(value: T) -> *mut () {
+    Box::into_raw(Box::new(value)) as *mut ()
+}
+
+#[test]
+fn test_erased() {
+    drop(Erased {
+        payload: erase(Payload(vec![1, 2, 3])),
+        drop_fn: drop_payload,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+
+    unsafe fn unreachable(_: *mut ()) {
+        unreachable!("null payloads are not dropped");
+    }
+    drop(Erased {
+        payload: std::ptr::null_mut(),
+        drop_fn: unreachable,
+    });
+}
@@ -0,0 +1,25 @@
+#![feature(prelude_import)]
+extern crate std;
+#[prelude_import]
+use std::prelude::rust_2021::*;
+use ffi_destruct::Destruct;
+
+/// Nothing to free, so `drop` has no `unsafe` block.
+pub struct Borrowed {
+    #[no_drop]
+    data: *mut u8,
+    len: usize,
+}
+const _: () =
+    {
+        #[deprecated(note =
+        "`Borrowed` has no pointer fields, the derived `Drop` is empty and prevents `Copy`; use `#[destruct(pod_ok)]` to skip generating it")]
+        const POINTERLESS_DESTRUCT: () = ();
+        POINTERLESS_DESTRUCT
+    };
+#[allow(unused_unsafe)]
+impl ::std::ops::Drop for Borrowed {
+    fn drop(&mut self) {}
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use ffi_destruct::Destruct;
+
+/// Nothing to free, so `drop` has no `unsafe` block.
+#[derive(Destruct)]
+pub struct Borrowed {
+    #[no_drop]
+    data: *mut u8,
+    len: usize,
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+#![feature(prelude_import)]
+extern crate std;
+#[prelude_import]
+use std::prelude::rust_2021::*;
+use ffi_destruct::Destruct;
+use std::ffi::c_char;
+
+struct Pointers {
+    const_string: *const c_char,
+    mut_string: *mut c_char,
+    const_byte: *const u8,
+    mut_byte: *mut u8,
+}
+#[allow(unused_unsafe)]
+impl ::std::ops::Drop for Pointers {
+    fn drop(&mut self) {
+        unsafe {
+            let _ =
+                ::std::ffi::CString::from_raw(self.const_string as
+                        *mut ::std::ffi::c_char);
+            let _ = ::std::ffi::CString::from_raw(self.mut_string);
+            let _ = ::std::boxed::Box::from_raw(self.const_byte as *mut u8);
+            let _ = ::std::boxed::Box::from_raw(self.mut_byte);
+        }
+    }
+}
+
+fn main() {}
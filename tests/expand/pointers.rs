@@ -0,0 +1,12 @@
+use ffi_destruct::Destruct;
+use std::ffi::c_char;
+
+#[derive(Destruct)]
+struct Pointers {
+    const_string: *const c_char,
+    mut_string: *mut c_char,
+    const_byte: *const u8,
+    mut_byte: *mut u8,
+}
+
+fn main() {}
@@ -0,0 +1,109 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::{Arc, Mutex};
+
+/// A writer that loses its buffered data unless flushed.
+struct Buffered {
+    pending: Vec<u8>,
+    sink: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Buffered {
+    fn flush(&mut self) -> Result<(), ()> {
+        self.sink.lock().unwrap().append(&mut self.pending);
+        Ok(())
+    }
+}
+
+#[derive(Destruct)]
+struct Writer {
+    #[nullable]
+    #[flush_before_drop = "flush"]
+    writer: *mut Buffered,
+}
+
+#[test]
+fn test_flush_before_drop() {
+    let sink = Arc::new(Mutex::new(Vec::new()));
+    let writer = Buffered {
+        pending: b"buffered".to_vec(),
+        sink: sink.clone(),
+    };
+
+    drop(Writer {
+        writer: Box::into_raw(Box::new(writer)),
+    });
+    assert_eq!(&*sink.lock().unwrap(), b"buffered");
+
+    drop(Writer {
+        writer: std::ptr::null_mut(),
+    });
+}
+
+/// Holds a handle which must be released before the memory is reclaimed.
+struct Handle {
+    closed: Arc<Mutex<bool>>,
+}
+
+impl Handle {
+    fn close(&mut self) {
+        *self.closed.lock().unwrap() = true;
+    }
+}
+
+#[derive(Destruct)]
+struct Connection {
+    #[nullable]
+    #[pre_drop = "close"]
+    handle: *mut Handle,
+}
+
+#[test]
+fn test_pre_drop() {
+    let closed = Arc::new(Mutex::new(false));
+    drop(Connection {
+        handle: Box::into_raw(Box::new(Handle {
+            closed: closed.clone(),
+        })),
+    });
+    assert!(*closed.lock().unwrap());
+
+    drop(Connection {
+        handle: std::ptr::null_mut(),
+    });
+}
+
+/// Records the calls before its memory is reclaimed.
+struct Stream {
+    calls: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Stream {
+    fn flush(&mut self) -> Result<(), ()> {
+        self.calls.lock().unwrap().push("flush");
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.calls.lock().unwrap().push("close");
+    }
+}
+
+#[derive(Destruct)]
+struct Session {
+    #[pre_drop = "close"]
+    #[flush_before_drop = "flush"]
+    stream: *mut Stream,
+}
+
+#[test]
+fn test_flush_then_pre_drop() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    drop(Session {
+        stream: Box::into_raw(Box::new(Stream {
+            calls: calls.clone(),
+        })),
+    });
+    assert_eq!(*calls.lock().unwrap(), ["flush", "close"]);
+}
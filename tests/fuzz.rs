@@ -0,0 +1,52 @@
+#![cfg(feature = "fuzz")]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{fuzz_log, Destruct};
+use std::ffi::*;
+
+fuzz_log!();
+
+struct Data(u32);
+
+#[derive(Destruct)]
+#[destruct(fuzz)]
+struct Recorded {
+    name: *mut c_char,
+    #[nullable]
+    data: *mut Data,
+}
+
+#[derive(Destruct)]
+#[destruct(fuzz)]
+struct Other {
+    data: *mut Data,
+}
+
+#[derive(Destruct)]
+struct Unrecorded {
+    data: *mut Data,
+}
+
+#[test]
+fn test_fuzz_log() {
+    // Once for each free, in the order of the frees across types
+    let mut freed = fuzz::freed_addresses().lock().unwrap();
+    freed.clear();
+    drop(freed);
+    let name = CString::new("recorded").unwrap().into_raw();
+    let data = Box::into_raw(Box::new(Data(1)));
+    drop(Recorded { name, data });
+    let other = Box::into_raw(Box::new(Data(2)));
+    drop(Other { data: other });
+    drop(Recorded {
+        name: CString::new("recorded").unwrap().into_raw(),
+        data: std::ptr::null_mut(),
+    });
+    drop(Unrecorded {
+        data: Box::into_raw(Box::new(Data(3))),
+    });
+
+    let freed = fuzz::freed_addresses().lock().unwrap();
+    assert_eq!(freed.len(), 4);
+    assert_eq!(freed[..3], [name as usize, data as usize, other as usize]);
+}
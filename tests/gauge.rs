@@ -0,0 +1,139 @@
+#![cfg(feature = "gauge")]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_constructor, extern_c_destructor, gauge_registry, Destruct};
+use std::ffi::*;
+use std::sync::atomic::Ordering;
+
+gauge_registry!();
+
+#[derive(Destruct)]
+struct Tracked {
+    name: *mut c_char,
+}
+
+fn tracked() -> Tracked {
+    Tracked::live_gauge().fetch_add(1, Ordering::Relaxed);
+    Tracked {
+        name: CString::new("tracked").unwrap().into_raw(),
+    }
+}
+
+#[test]
+fn test_gauge() {
+    let a = tracked();
+    let b = tracked();
+    assert_eq!(Tracked::live_gauge().load(Ordering::Relaxed), 2);
+    drop(a);
+    assert_eq!(Tracked::live_gauge().load(Ordering::Relaxed), 1);
+    drop(b);
+    assert_eq!(Tracked::live_gauge().load(Ordering::Relaxed), 0);
+
+    // Objects constructed without the gauge do not underflow it
+    drop(Tracked {
+        name: CString::new("untracked").unwrap().into_raw(),
+    });
+    assert_eq!(Tracked::live_gauge().load(Ordering::Relaxed), 0);
+}
+
+#[derive(Destruct, Default)]
+pub struct Allocated {
+    #[nullable]
+    name: *mut c_char,
+}
+
+extern_c_constructor!(Allocated);
+extern_c_destructor!(Allocated);
+
+#[test]
+fn test_gauge_constructor() {
+    unsafe {
+        let allocated = new_allocated();
+        assert_eq!(Allocated::live_gauge().load(Ordering::Relaxed), 1);
+        destruct_allocated(allocated);
+    }
+    assert_eq!(Allocated::live_gauge().load(Ordering::Relaxed), 0);
+}
+
+#[derive(Destruct, Default)]
+#[destruct(gauge)]
+pub struct Registered {
+    #[nullable]
+    name: *mut c_char,
+}
+
+extern_c_constructor!(Registered);
+extern_c_destructor!(Registered);
+
+#[test]
+fn test_gauge_registry() {
+    // Registered by path on first use, only the opted in types
+    assert_eq!(gauge::live("gauge::Registered"), None);
+    unsafe {
+        let first = new_registered();
+        let second = new_registered();
+        assert_eq!(gauge::live("gauge::Registered"), Some(2));
+        destruct_registered(first);
+        assert!(gauge::snapshot().contains(&("gauge::Registered", 1)));
+        destruct_registered(second);
+    }
+    assert_eq!(gauge::live("gauge::Registered"), Some(0));
+    assert_eq!(gauge::live("gauge::Allocated"), None);
+}
+
+mod other {
+    use super::*;
+
+    // Same name as the other `Registered`, but a gauge of its own
+    #[derive(Destruct, Default)]
+    #[destruct(gauge)]
+    pub struct Registered {
+        #[nullable]
+        name: *mut c_char,
+    }
+
+    #[test]
+    fn test_gauge_path() {
+        Registered::live_gauge().fetch_add(1, Ordering::Relaxed);
+        assert_eq!(gauge::live("gauge::other::Registered"), Some(1));
+        drop(Registered::default());
+        assert_eq!(gauge::live("gauge::other::Registered"), Some(0));
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(consumed_flag = "consumed")]
+struct Consumed {
+    consumed: bool,
+    name: *mut c_char,
+}
+
+#[test]
+fn test_gauge_early_return() {
+    // Dropped without freeing, but no longer live
+    Consumed::live_gauge().fetch_add(1, Ordering::Relaxed);
+    let name = CString::new("consumed").unwrap().into_raw();
+    drop(Consumed {
+        consumed: true,
+        name,
+    });
+    assert_eq!(Consumed::live_gauge().load(Ordering::Relaxed), 0);
+    drop(unsafe { CString::from_raw(name) });
+}
+
+#[derive(Destruct, Default)]
+#[destruct(pod_ok)]
+pub struct Plain {
+    value: u32,
+}
+
+extern_c_constructor!(Plain);
+
+#[test]
+fn test_gauge_pod() {
+    unsafe {
+        let plain = new_plain();
+        assert_eq!(Plain::live_gauge().load(Ordering::Relaxed), 1);
+        drop(Box::from_raw(plain));
+    }
+}
@@ -0,0 +1,61 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Wrapper<'a, T> {
+    ptr: *mut T,
+    _m: PhantomData<&'a T>,
+}
+
+#[derive(Destruct)]
+struct Buf<const N: usize> {
+    // A bare identifier names a field, so the parameter is parenthesized
+    #[array(len = "(N)")]
+    data: *mut u8,
+}
+
+#[derive(Destruct)]
+#[destruct(report)]
+struct Bounded<T>
+where
+    T: Default,
+{
+    #[nullable]
+    ptr: *mut T,
+}
+
+#[test]
+fn test_generics() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    drop(Wrapper {
+        ptr: Box::into_raw(Box::new(Counted)),
+        _m: PhantomData,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+
+    drop(Buf::<4> {
+        data: Box::into_raw(vec![0u8; 4].into_boxed_slice()) as *mut u8,
+    });
+
+    let bounded = Bounded {
+        ptr: Box::into_raw(Box::new(1u32)),
+    };
+    assert_eq!(bounded.destruct_count(), 1);
+    drop(bounded);
+    drop(Bounded::<u32> {
+        ptr: std::ptr::null_mut(),
+    });
+}
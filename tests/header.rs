@@ -0,0 +1,62 @@
+#![cfg(feature = "header")]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{
+    atexit_destructor, destruct_result, extern_c_clone, extern_c_constructor, extern_c_destructor,
+    Destruct,
+};
+use std::sync::atomic::AtomicPtr;
+
+destruct_result!();
+
+/// An opaque handle, C only knows its name.
+#[derive(Destruct, Default, Clone)]
+#[destruct(report)]
+pub struct Handle {
+    #[nullable]
+    buffer: *mut u8,
+    #[nullable]
+    name: *mut std::ffi::c_char,
+}
+
+extern_c_constructor!(Handle);
+extern_c_clone!(Handle);
+extern_c_destructor!(Handle);
+extern_c_destructor!(Handle, array);
+extern_c_destructor!(
+    Handle,
+    name = "handle_free_checked",
+    checked,
+    report(out = "freed")
+);
+extern_c_destructor!(Handle, name = "handle_free_result", result);
+
+static GLOBAL_HANDLE: AtomicPtr<Handle> = AtomicPtr::new(std::ptr::null_mut());
+
+atexit_destructor!(GLOBAL_HANDLE);
+
+#[test]
+fn test_header() {
+    let header = [
+        "typedef struct Handle Handle;",
+        NEW_HANDLE_PROTOTYPE,
+        CLONE_HANDLE_PROTOTYPE,
+        DESTRUCT_HANDLE_PROTOTYPE,
+        DESTRUCT_HANDLE_ARRAY_PROTOTYPE,
+        HANDLE_FREE_CHECKED_PROTOTYPE,
+        HANDLE_FREE_RESULT_PROTOTYPE,
+        ATEXIT_GLOBAL_HANDLE_PROTOTYPE,
+    ]
+    .join("\n");
+    assert_eq!(
+        header,
+        "typedef struct Handle Handle;\n\
+         Handle *new_handle(void);\n\
+         Handle *clone_handle(const Handle *ptr);\n\
+         void destruct_handle(Handle *ptr);\n\
+         void destruct_handle_array(Handle *ptr, size_t n);\n\
+         int handle_free_checked(Handle *ptr, size_t *freed);\n\
+         DestructResult handle_free_result(Handle *ptr);\n\
+         void atexit_global_handle(void);"
+    );
+}
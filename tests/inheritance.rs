@@ -0,0 +1,68 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::*;
+use std::sync::Mutex;
+
+static EVENTS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+struct Resource(&'static str);
+
+impl Drop for Resource {
+    fn drop(&mut self) {
+        EVENTS.lock().unwrap().push(self.0);
+    }
+}
+
+#[derive(Destruct)]
+#[repr(C)]
+pub struct Base {
+    name: *mut c_char,
+    resource: *mut Resource,
+}
+
+/// Embeds the base by value, as `struct Derived { struct Base base; ... }` in C.
+#[derive(Destruct)]
+#[repr(C)]
+pub struct DerivedValue {
+    base: Base,
+    extra: *mut Resource,
+}
+
+/// References the base by pointer, as `struct Derived { struct Base *base; ... }` in C.
+#[derive(Destruct)]
+#[repr(C)]
+pub struct DerivedPointer {
+    base: *mut Base,
+    extra: *mut Resource,
+}
+
+fn base(resource: &'static str) -> Base {
+    Base {
+        name: CString::new("base").unwrap().into_raw(),
+        resource: Box::into_raw(Box::new(Resource(resource))),
+    }
+}
+
+#[test]
+fn test_inheritance() {
+    // The derived destructor frees its own pointers, then the embedded base is dropped.
+    drop(DerivedValue {
+        base: base("value base"),
+        extra: Box::into_raw(Box::new(Resource("value extra"))),
+    });
+    assert_eq!(
+        std::mem::take(&mut *EVENTS.lock().unwrap()),
+        ["value extra", "value base"]
+    );
+
+    // Pointer fields are freed in declaration order, the base first.
+    drop(DerivedPointer {
+        base: Box::into_raw(Box::new(base("pointer base"))),
+        extra: Box::into_raw(Box::new(Resource("pointer extra"))),
+    });
+    assert_eq!(
+        std::mem::take(&mut *EVENTS.lock().unwrap()),
+        ["pointer base", "pointer extra"]
+    );
+}
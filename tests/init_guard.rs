@@ -0,0 +1,49 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Lazy {
+    #[init_guard = "once"]
+    value: *mut Counted,
+    once: Once,
+}
+
+impl Lazy {
+    fn get(&mut self) -> *mut Counted {
+        let value = &mut self.value;
+        self.once
+            .call_once(|| *value = Box::into_raw(Box::new(Counted)));
+        self.value
+    }
+}
+
+#[test]
+fn test_init_guard() {
+    // Never initialized, the dangling pointer is not freed
+    drop(Lazy {
+        value: std::ptr::NonNull::dangling().as_ptr(),
+        once: Once::new(),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 0);
+
+    let mut lazy = Lazy {
+        value: std::ptr::NonNull::dangling().as_ptr(),
+        once: Once::new(),
+    };
+    lazy.get();
+    drop(lazy);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+}
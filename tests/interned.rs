@@ -0,0 +1,56 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::*;
+use std::sync::Mutex;
+
+/// The intern pool, owning the strings until the end of the program.
+static POOL: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+fn intern(s: &str) -> *const c_char {
+    let ptr = CString::new(s).unwrap().into_raw();
+    POOL.lock().unwrap().push(ptr as usize);
+    ptr
+}
+
+fn is_interned(ptr: *const c_char) -> bool {
+    POOL.lock().unwrap().contains(&(ptr as usize))
+}
+
+#[derive(Destruct)]
+struct Symbol {
+    #[interned = "is_interned"]
+    name: *const c_char,
+    #[interned]
+    kind: *const c_char,
+    owned: *mut c_char,
+}
+
+#[test]
+fn test_interned() {
+    let name = intern("name");
+    let kind = intern("kind");
+    drop(Symbol {
+        name,
+        kind,
+        owned: CString::new("owned").unwrap().into_raw(),
+    });
+    drop(Symbol {
+        name,
+        kind,
+        owned: CString::new("owned").unwrap().into_raw(),
+    });
+    assert_eq!(unsafe { CStr::from_ptr(name) }.to_str(), Ok("name"));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "`name` is not interned")]
+fn test_not_interned() {
+    let name = CString::new("name").unwrap();
+    drop(Symbol {
+        name: name.as_ptr(),
+        kind: intern("kind"),
+        owned: CString::new("owned").unwrap().into_raw(),
+    });
+}
@@ -0,0 +1,65 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(iterative, child = "children", len = "n")]
+struct Node {
+    #[nullable]
+    value: *mut Counted,
+    children: *mut *mut Node,
+    n: u32,
+}
+
+fn node(value: *mut Counted, children: Vec<*mut Node>) -> *mut Node {
+    let n = children.len() as u32;
+    let children = if children.is_empty() {
+        std::ptr::null_mut()
+    } else {
+        Box::into_raw(children.into_boxed_slice()) as *mut *mut Node
+    };
+    Box::into_raw(Box::new(Node { value, children, n }))
+}
+
+#[test]
+fn test_iterative() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let counted = || Box::into_raw(Box::new(Counted));
+    let tree = node(
+        counted(),
+        vec![
+            node(counted(), vec![]),
+            node(
+                counted(),
+                vec![node(counted(), vec![]), node(counted(), vec![])],
+            ),
+        ],
+    );
+    unsafe {
+        let _ = Box::from_raw(tree);
+    }
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 5);
+}
+
+#[test]
+fn test_iterative_deep() {
+    // Deep enough to overflow the stack with recursive drops
+    let mut tree = node(std::ptr::null_mut(), vec![]);
+    for _ in 0..1_000_000 {
+        tree = node(std::ptr::null_mut(), vec![tree]);
+    }
+    unsafe {
+        let _ = Box::from_raw(tree);
+    }
+}
@@ -0,0 +1,43 @@
+#![deny(unused_unsafe, clippy::all)]
+#![allow(dead_code, deprecated)]
+
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct External {
+    #[no_drop]
+    borrowed: *mut u8,
+    #[no_drop]
+    other: *const u8,
+}
+
+#[derive(Destruct)]
+struct Casted {
+    #[array(len = "len")]
+    items: *mut u8,
+    len: usize,
+}
+
+#[derive(Destruct)]
+#[destruct(manual, builder)]
+struct Manual {
+    #[nullable]
+    data: *mut u8,
+}
+
+#[test]
+fn test_lints() {
+    let mut byte = 0;
+    drop(External {
+        borrowed: &mut byte,
+        other: &byte,
+    });
+    drop(Casted {
+        items: std::ptr::null_mut(),
+        len: 0,
+    });
+    Manual {
+        data: std::ptr::null_mut(),
+    }
+    .destruct();
+}
@@ -0,0 +1,52 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(report)]
+struct Locked {
+    mutex: Mutex<*mut Counted>,
+    #[nullable]
+    rwlock: RwLock<*mut Counted>,
+    name: std::sync::Mutex<*mut std::ffi::c_char>,
+}
+
+#[test]
+fn test_locked_pointers() {
+    let locked = Locked {
+        mutex: Mutex::new(Box::into_raw(Box::new(Counted))),
+        rwlock: RwLock::new(Box::into_raw(Box::new(Counted))),
+        name: Mutex::new(std::ffi::CString::new("name").unwrap().into_raw()),
+    };
+    assert_eq!(locked.destruct_count(), 3);
+    drop(locked);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+
+    let locked = Locked {
+        mutex: Mutex::new(Box::into_raw(Box::new(Counted))),
+        rwlock: RwLock::new(std::ptr::null_mut()),
+        name: Mutex::new(std::ffi::CString::new("name").unwrap().into_raw()),
+    };
+    assert_eq!(locked.destruct_count(), 2);
+
+    // A poisoned lock is freed as well
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _guard = locked.mutex.lock().unwrap();
+        panic!("poison the lock");
+    }));
+    assert!(locked.mutex.is_poisoned());
+    drop(locked);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,71 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(log = "debug")]
+struct Logged {
+    a: *mut Counted,
+}
+
+#[test]
+#[cfg(not(feature = "log"))]
+fn test_log_ignored() {
+    // Without the feature, nothing references the `log` crate
+    drop(Logged {
+        a: Box::into_raw(Box::new(Counted)),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+}
+
+/// Records the level and message of each record, in logging order.
+#[cfg(feature = "log")]
+struct Capture {
+    records: Mutex<Vec<(log::Level, String)>>,
+}
+
+#[cfg(feature = "log")]
+impl log::Log for Capture {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let message = record.args().to_string();
+        self.records.lock().unwrap().push((record.level(), message));
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(feature = "log")]
+static LOGGER: Capture = Capture {
+    records: Mutex::new(Vec::new()),
+};
+
+#[test]
+#[cfg(feature = "log")]
+fn test_log() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+    drop(Logged {
+        a: Box::into_raw(Box::new(Counted)),
+    });
+    assert_eq!(
+        *LOGGER.records.lock().unwrap(),
+        [(log::Level::Debug, "dropping Logged".to_string())]
+    );
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+}
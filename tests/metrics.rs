@@ -0,0 +1,91 @@
+#![cfg(feature = "metrics")]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Records the counters, keyed by their name and labels, e.g. `freed{type=Structure}`.
+#[derive(Default)]
+struct Capture {
+    counters: Mutex<Vec<(String, Arc<AtomicU64>)>>,
+}
+
+impl Capture {
+    /// The value of the counter, `0` if never incremented.
+    fn value(&self, key: &str) -> u64 {
+        let counters = self.counters.lock().unwrap();
+        counters
+            .iter()
+            .find(|(other, _)| other == key)
+            .map_or(0, |(_, value)| value.load(Ordering::SeqCst))
+    }
+}
+
+impl Recorder for Capture {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        let labels = key
+            .labels()
+            .map(|label| format!("{}={}", label.key(), label.value()))
+            .collect::<Vec<_>>();
+        let name = format!("{}{{{}}}", key.name(), labels.join(","));
+        let mut counters = self.counters.lock().unwrap();
+        let counter = match counters.iter().find(|(other, _)| *other == name) {
+            Some((_, counter)) => counter.clone(),
+            None => {
+                let counter = Arc::new(AtomicU64::new(0));
+                counters.push((name, counter.clone()));
+                counter
+            }
+        };
+        Counter::from_arc(counter)
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+#[derive(Destruct)]
+struct Counted {
+    first: *mut u8,
+    #[nullable]
+    missing: *mut u8,
+    second: *mut u8,
+}
+
+#[derive(Destruct)]
+struct Other {
+    a: *mut u8,
+}
+
+#[test]
+fn test_metrics() {
+    let counted = || Counted {
+        first: Box::into_raw(Box::new(1)),
+        missing: std::ptr::null_mut(),
+        second: Box::into_raw(Box::new(2)),
+    };
+    let capture = Capture::default();
+    metrics::with_local_recorder(&capture, || {
+        drop(counted());
+        drop(counted());
+        drop(Other {
+            a: Box::into_raw(Box::new(3)),
+        });
+    });
+    // Labeled with the type, null pointers are not counted
+    assert_eq!(capture.value("ffi_destruct_freed{type=Counted}"), 4);
+    assert_eq!(capture.value("ffi_destruct_freed{type=Other}"), 1);
+}
@@ -0,0 +1,44 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_destructor, Destruct};
+use std::ffi::{c_char, CString};
+
+#[derive(Destruct)]
+pub struct TypeA {
+    name: *mut c_char,
+}
+
+#[derive(Destruct)]
+pub struct TypeB {
+    value: *mut u32,
+}
+
+#[derive(Destruct)]
+pub struct TypeC {
+    #[nullable]
+    value: *mut u64,
+}
+
+extern_c_destructor!(TypeA, TypeB, TypeC);
+extern_c_destructor!(TypeA, TypeB, array, returns_bool, abi = "C-unwind");
+
+#[test]
+fn test_multiple_types() {
+    unsafe {
+        destruct_type_a(Box::into_raw(Box::new(TypeA {
+            name: CString::new("a").unwrap().into_raw(),
+        })));
+        destruct_type_b(Box::into_raw(Box::new(TypeB {
+            value: Box::into_raw(Box::new(1)),
+        })));
+        destruct_type_c(std::ptr::null_mut());
+
+        // The options apply to each type
+        let values = vec![TypeB {
+            value: Box::into_raw(Box::new(2)),
+        }];
+        let values = Box::into_raw(values.into_boxed_slice()) as *mut TypeB;
+        assert!(destruct_type_b_array(values, 1));
+        assert!(!destruct_type_a_array(std::ptr::null_mut(), 0));
+    }
+}
@@ -0,0 +1,63 @@
+//! Derives in a `no_std` crate with `alloc`: `cargo test --manifest-path tests/no_std/Cargo.toml`
+#![no_std]
+#![allow(dead_code, unused)]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::vec;
+use core::ffi::c_char;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ffi_destruct::{extern_c_constructor, extern_c_destructor, Destruct};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Default)]
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(report)]
+pub struct Embedded {
+    name: *mut c_char,
+    #[nullable]
+    data: *mut Counted,
+    #[array(len = "len")]
+    items: *mut Counted,
+    len: usize,
+}
+
+extern_c_destructor!(Embedded);
+
+#[derive(Destruct, Default)]
+pub struct Empty {
+    #[nullable]
+    data: *mut Counted,
+}
+
+extern_c_constructor!(Empty);
+extern_c_destructor!(Empty);
+
+#[test]
+fn test_no_std() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let items = vec![Counted, Counted].into_boxed_slice();
+    let embedded = Box::new(Embedded {
+        name: CString::new("embedded").unwrap().into_raw(),
+        data: Box::into_raw(Box::new(Counted)),
+        items: Box::into_raw(items) as *mut Counted,
+        len: 2,
+    });
+    assert_eq!(embedded.destruct_count(), 4);
+    unsafe {
+        destruct_embedded(Box::into_raw(embedded));
+        destruct_empty(new_empty());
+    }
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 3);
+}
@@ -0,0 +1,52 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, CString};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Handles {
+    value: NonNull<Counted>,
+    optional: Option<NonNull<Counted>>,
+    name: NonNull<c_char>,
+    description: Option<NonNull<c_char>>,
+}
+
+fn counted() -> NonNull<Counted> {
+    NonNull::from(Box::leak(Box::new(Counted)))
+}
+
+fn c_string(value: &str) -> NonNull<c_char> {
+    NonNull::new(CString::new(value).unwrap().into_raw()).unwrap()
+}
+
+#[test]
+fn test_non_null() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    drop(Handles {
+        value: counted(),
+        optional: Some(counted()),
+        name: c_string("name"),
+        description: Some(c_string("description")),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+
+    drop(Handles {
+        value: counted(),
+        optional: None,
+        name: c_string("name"),
+        description: None,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 3);
+}
@@ -0,0 +1,37 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::*;
+use std::sync::mpsc;
+
+#[derive(Destruct)]
+#[destruct(notify_sender = "sender")]
+struct Notifying {
+    name: *mut c_char,
+    sender: mpsc::Sender<()>,
+}
+
+// The name is owned and never shared
+unsafe impl Send for Notifying {}
+
+#[test]
+fn test_notify_sender() {
+    let (sender, receiver) = mpsc::channel();
+    let object = Notifying {
+        name: CString::new("notifying").unwrap().into_raw(),
+        sender,
+    };
+    assert!(receiver.try_recv().is_err());
+
+    let handle = std::thread::spawn(move || drop(object));
+    receiver.recv().unwrap();
+    handle.join().unwrap();
+
+    // Fire-and-forget, even without a receiver
+    let (sender, receiver) = mpsc::channel();
+    drop(receiver);
+    drop(Notifying {
+        name: CString::new("notifying").unwrap().into_raw(),
+        sender,
+    });
+}
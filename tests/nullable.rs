@@ -0,0 +1,52 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, CString};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(nullable)]
+struct Optional {
+    name: *mut c_char,
+    data: *mut Counted,
+    #[non_null]
+    required: *mut Counted,
+    #[no_drop]
+    borrowed: *mut Counted,
+    len: u32,
+}
+
+#[test]
+fn test_nullable_default() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let counted = || Box::into_raw(Box::new(Counted));
+    let mut borrowed = Counted;
+    drop(Optional {
+        name: CString::new("name").unwrap().into_raw(),
+        data: counted(),
+        required: counted(),
+        borrowed: &mut borrowed,
+        len: 0,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+
+    // Every pointer but `required` may be null
+    drop(Optional {
+        name: std::ptr::null_mut(),
+        data: std::ptr::null_mut(),
+        required: counted(),
+        borrowed: std::ptr::null_mut(),
+        len: 0,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 3);
+}
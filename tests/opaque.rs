@@ -0,0 +1,51 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_destructor, Destruct};
+use std::ffi::*;
+
+/// Never defined on the C side: `typedef struct Handle Handle;`
+#[derive(Destruct)]
+pub struct Handle {
+    name: *mut c_char,
+    #[nullable]
+    parent: *mut Handle,
+    refs: Vec<u32>,
+}
+
+/// `Handle *handle_new(const char *name, Handle *parent);`
+///
+/// # Safety
+/// `name` must be a C string, and `parent` null or a handle, which the new handle takes over.
+#[no_mangle]
+pub unsafe extern "C" fn handle_new(name: *const c_char, parent: *mut Handle) -> *mut Handle {
+    Box::into_raw(Box::new(Handle {
+        name: CStr::from_ptr(name).to_owned().into_raw(),
+        parent,
+        refs: vec![1, 2, 3],
+    }))
+}
+
+/// `const char *handle_name(const Handle *handle);`
+///
+/// # Safety
+/// `handle` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn handle_name(handle: *const Handle) -> *const c_char {
+    (*handle).name
+}
+
+// `void destruct_handle(Handle *ptr);`
+extern_c_destructor!(Handle);
+
+#[test]
+fn test_opaque_handle() {
+    // Used like C would, only through the pointers
+    unsafe {
+        let root = handle_new(c"root".as_ptr(), std::ptr::null_mut());
+        let child = handle_new(c"child".as_ptr(), root);
+        assert_eq!(CStr::from_ptr(handle_name(child)), c"child");
+        // The child owns its parent
+        destruct_handle(child);
+        destruct_handle(std::ptr::null_mut());
+    }
+}
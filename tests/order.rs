@@ -0,0 +1,103 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Logs its drop, like a `File` flushing on close.
+struct Owned {
+    name: &'static str,
+    log: *const Mutex<Vec<String>>,
+}
+
+impl Drop for Owned {
+    fn drop(&mut self) {
+        let log = unsafe { &*self.log };
+        log.lock().unwrap().push(format!("owned {}", self.name));
+    }
+}
+
+#[derive(Destruct)]
+struct Mixed {
+    before: Owned,
+    pointer: *mut Owned,
+    after: Owned,
+}
+
+#[derive(Destruct)]
+struct Pointers {
+    pointer: *mut Owned,
+}
+
+/// Frees the pointers after `owned`, through the nested structure.
+struct Nested {
+    owned: Owned,
+    pointers: Pointers,
+}
+
+#[test]
+fn test_owned_field_order() {
+    let log = Mutex::new(Vec::new());
+    let owned = |name| Owned { name, log: &log };
+
+    // The pointers are freed by `Drop::drop`, before any field is dropped
+    drop(Mixed {
+        before: owned("before"),
+        pointer: Box::into_raw(Box::new(owned("pointer"))),
+        after: owned("after"),
+    });
+    // Declared after the owned field, the nested structure frees its pointers last
+    drop(Nested {
+        owned: owned("field"),
+        pointers: Pointers {
+            pointer: Box::into_raw(Box::new(owned("nested pointer"))),
+        },
+    });
+    assert_eq!(
+        log.into_inner().unwrap(),
+        [
+            "owned pointer",
+            "owned before",
+            "owned after",
+            "owned field",
+            "owned nested pointer",
+        ]
+    );
+}
+
+static SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+/// Records the position of its drop in the sequence.
+struct Step(*mut usize);
+
+impl Drop for Step {
+    fn drop(&mut self) {
+        unsafe { *self.0 = SEQUENCE.fetch_add(1, Ordering::SeqCst) };
+    }
+}
+
+#[derive(Destruct)]
+struct Ties {
+    #[drop_order(2)]
+    last: *mut Step,
+    first: *mut Step,
+    second: *mut Step,
+    #[drop_order(2)]
+    after_last: *mut Step,
+}
+
+#[test]
+fn test_drop_order_ties() {
+    let mut steps = [0; 4];
+    let step = |slot: &mut usize| Box::into_raw(Box::new(Step(slot)));
+    let [last, first, second, after_last] = &mut steps;
+    drop(Ties {
+        last: step(last),
+        first: step(first),
+        second: step(second),
+        after_last: step(after_last),
+    });
+    // Sorted by priority, the declaration order breaks the ties
+    let start = steps.iter().min().copied().unwrap();
+    assert_eq!(steps.map(|step| step - start), [2, 0, 1, 3]);
+}
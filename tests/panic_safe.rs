@@ -0,0 +1,45 @@
+#![cfg(feature = "panic-safe")]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted {
+    panics: bool,
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+        if self.panics {
+            panic!("pointee drop panicked");
+        }
+    }
+}
+
+#[derive(Destruct)]
+struct Fields {
+    first: *mut Counted,
+    panicking: *mut Counted,
+    last: *mut Counted,
+}
+
+#[test]
+fn test_panic_safe() {
+    let counted = |panics| Box::into_raw(Box::new(Counted { panics }));
+    let fields = Fields {
+        first: counted(false),
+        panicking: counted(true),
+        last: counted(false),
+    };
+    let result = std::panic::catch_unwind(move || drop(fields));
+    // The panic is resumed after the last field is freed
+    let payload = result.unwrap_err();
+    assert_eq!(
+        payload.downcast_ref::<&str>(),
+        Some(&"pointee drop panicked")
+    );
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+}
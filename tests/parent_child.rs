@@ -0,0 +1,92 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::Mutex;
+
+/// Ids of the registered children, owned by the parent.
+struct Registry {
+    ids: Vec<u32>,
+    log: *const Mutex<Vec<String>>,
+}
+
+impl Drop for Registry {
+    fn drop(&mut self) {
+        let log = unsafe { &*self.log };
+        log.lock().unwrap().push(format!("registry {:?}", self.ids));
+    }
+}
+
+struct Child {
+    id: u32,
+    // Back-pointer into the parent, dangling once the registry is freed
+    registry: *mut Registry,
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        let id = self.id;
+        let registry = unsafe { &mut *self.registry };
+        registry.ids.retain(|&other| other != id);
+        let log = unsafe { &*registry.log };
+        log.lock().unwrap().push(format!("child {}", id));
+    }
+}
+
+#[derive(Destruct)]
+struct Parent {
+    first: *mut Child,
+    second: *mut Child,
+    // Declared last, so freed after the children
+    registry: *mut Registry,
+}
+
+#[test]
+fn test_children_before_parent() {
+    let log = Mutex::new(Vec::new());
+    let registry = Box::into_raw(Box::new(Registry {
+        ids: vec![1, 2],
+        log: &log,
+    }));
+    let child = |id| Box::into_raw(Box::new(Child { id, registry }));
+
+    drop(Parent {
+        first: child(1),
+        second: child(2),
+        registry,
+    });
+    // The children are freed first, while the registry is still alive
+    assert_eq!(
+        log.into_inner().unwrap(),
+        ["child 1", "child 2", "registry []"]
+    );
+}
+
+/// The same parent, declared in any order and reordered with `#[drop_order]`.
+#[derive(Destruct)]
+struct OrderedParent {
+    #[drop_order(1)]
+    registry: *mut Registry,
+    first: *mut Child,
+    #[drop_order(-1)]
+    second: *mut Child,
+}
+
+#[test]
+fn test_drop_order() {
+    let log = Mutex::new(Vec::new());
+    let registry = Box::into_raw(Box::new(Registry {
+        ids: vec![1, 2],
+        log: &log,
+    }));
+    let child = |id| Box::into_raw(Box::new(Child { id, registry }));
+
+    drop(OrderedParent {
+        registry,
+        first: child(1),
+        second: child(2),
+    });
+    assert_eq!(
+        log.into_inner().unwrap(),
+        ["child 2", "child 1", "registry []"]
+    );
+}
@@ -0,0 +1,49 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::*;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[repr(C)]
+pub struct Structure {
+    #[owned]
+    name: *mut c_char,
+    #[owned]
+    #[nullable]
+    data: *mut Counted,
+    other: *mut Counted,
+}
+
+#[test]
+fn test_destruct_partial() {
+    // Only the owned fields are initialized
+    let mut uninit = Box::new(MaybeUninit::<Structure>::uninit());
+    let ptr = uninit.as_mut_ptr();
+    unsafe {
+        std::ptr::addr_of_mut!((*ptr).name).write(CString::new("partial").unwrap().into_raw());
+        std::ptr::addr_of_mut!((*ptr).data).write(Box::into_raw(Box::new(Counted)));
+        Structure::destruct_partial(Box::into_raw(uninit) as *mut Structure);
+        Structure::destruct_partial(std::ptr::null_mut());
+    }
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+
+    // The complete destructor frees everything
+    drop(Structure {
+        name: CString::new("complete").unwrap().into_raw(),
+        data: Box::into_raw(Box::new(Counted)),
+        other: Box::into_raw(Box::new(Counted)),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,47 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_clone, extern_c_constructor, extern_c_destructor, Destruct};
+use std::ffi::CString;
+
+mod ffi {
+    use super::*;
+
+    #[derive(Destruct, Clone, Default)]
+    #[destruct(nullable)]
+    pub struct Structure {
+        pub c_string: *mut std::ffi::c_char,
+    }
+
+    #[derive(Destruct)]
+    pub struct Wrapper<T> {
+        pub value: *mut T,
+    }
+}
+
+extern_c_destructor!(crate::ffi::Structure);
+extern_c_clone!(ffi::Structure);
+extern_c_constructor!(self::ffi::Structure);
+extern_c_destructor!(ffi::Wrapper<u8>, name = "destruct_byte_wrapper");
+
+#[test]
+fn test_qualified_path() {
+    unsafe {
+        let structure = new_structure();
+        assert!((*structure).c_string.is_null());
+        (*structure).c_string = CString::new("c_string").unwrap().into_raw();
+        let clone = clone_structure(structure);
+        (*clone).c_string = CString::new("clone").unwrap().into_raw();
+        destruct_structure(structure);
+        destruct_structure(clone);
+    }
+}
+
+#[test]
+fn test_generic_path() {
+    let wrapper = ffi::Wrapper {
+        value: Box::into_raw(Box::new(7u8)),
+    };
+    unsafe {
+        destruct_byte_wrapper(Box::into_raw(Box::new(wrapper)));
+    }
+}
@@ -0,0 +1,45 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+
+#[derive(Destruct, Clone, Copy)]
+#[destruct(pod_ok)]
+struct Pod {
+    a: u32,
+    #[no_drop]
+    b: *const u8,
+}
+
+// Warns: the derived `Drop` is empty
+#[allow(deprecated)]
+mod not_pod {
+    use ffi_destruct::Destruct;
+
+    #[derive(Destruct)]
+    pub struct NotPod {
+        pub a: u32,
+    }
+}
+
+#[test]
+fn test_pod_ok() {
+    assert!(!std::mem::needs_drop::<Pod>());
+    let a = Pod {
+        a: 1,
+        b: std::ptr::null(),
+    };
+    let b = a;
+    assert_eq!(a.a, b.a);
+
+    assert!(std::mem::needs_drop::<not_pod::NotPod>());
+}
+
+#[derive(Destruct)]
+struct Empty;
+
+#[test]
+fn test_unit() {
+    // The `Drop` is empty but still derived
+    assert!(std::mem::needs_drop::<Empty>());
+    drop(Empty);
+}
@@ -0,0 +1,37 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(skip_if_poisoned = "poisoned")]
+struct Poisonable {
+    data: *mut Counted,
+    poisoned: bool,
+}
+
+#[test]
+fn test_skip_if_poisoned() {
+    drop(Poisonable {
+        data: Box::into_raw(Box::new(Counted)),
+        poisoned: false,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+
+    // Deliberately leaked
+    drop(Poisonable {
+        data: Box::into_raw(Box::new(Counted)),
+        poisoned: true,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+}
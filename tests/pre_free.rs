@@ -0,0 +1,46 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::{Arc, Mutex};
+
+trait PreFree {
+    unsafe fn pre_free(&mut self);
+}
+
+/// A child registered in a list shared with its parent.
+struct Child {
+    id: u32,
+    registry: Arc<Mutex<Vec<u32>>>,
+}
+
+impl PreFree for Child {
+    unsafe fn pre_free(&mut self) {
+        self.registry.lock().unwrap().retain(|&id| id != self.id);
+    }
+}
+
+#[derive(Destruct)]
+struct Parent {
+    #[nullable]
+    #[pre_free]
+    child: *mut Child,
+}
+
+#[test]
+fn test_pre_free() {
+    let registry = Arc::new(Mutex::new(vec![1, 2]));
+    let child = Child {
+        id: 1,
+        registry: registry.clone(),
+    };
+
+    drop(Parent {
+        child: Box::into_raw(Box::new(child)),
+    });
+    assert_eq!(&*registry.lock().unwrap(), &[2]);
+    assert_eq!(Arc::strong_count(&registry), 1);
+
+    drop(Parent {
+        child: std::ptr::null_mut(),
+    });
+}
@@ -0,0 +1,30 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_destructor, Destruct};
+
+#[repr(C)]
+#[derive(Destruct)]
+pub struct MyStruct {
+    value: *mut u32,
+}
+
+#[repr(C)]
+#[derive(Destruct)]
+pub struct Handle {
+    value: *mut u32,
+}
+
+extern_c_destructor!(MyStruct, prefix = "free_");
+extern_c_destructor!(Handle, prefix = "mylib_", case = "preserve");
+
+#[test]
+fn test_prefix() {
+    unsafe {
+        free_my_struct(Box::into_raw(Box::new(MyStruct {
+            value: Box::into_raw(Box::new(1)),
+        })));
+        mylib_Handle(Box::into_raw(Box::new(Handle {
+            value: Box::into_raw(Box::new(2)),
+        })));
+    }
+}
@@ -0,0 +1,35 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+struct Inner(u32);
+
+#[derive(Destruct)]
+struct Shared {
+    #[reclaim = "Rc<RefCell<Inner>>"]
+    local: *const RefCell<Inner>,
+    #[nullable]
+    #[reclaim = "std::sync::Arc<Inner>"]
+    sync: *const Inner,
+}
+
+#[test]
+fn test_reclaim() {
+    let local = Rc::new(RefCell::new(Inner(1)));
+    let sync = Arc::new(Inner(2));
+    drop(Shared {
+        local: Rc::into_raw(local.clone()),
+        sync: Arc::into_raw(sync.clone()),
+    });
+    assert_eq!(Rc::strong_count(&local), 1);
+    assert_eq!(Arc::strong_count(&sync), 1);
+
+    drop(Shared {
+        local: Rc::into_raw(local.clone()),
+        sync: std::ptr::null(),
+    });
+    assert_eq!(Rc::strong_count(&local), 1);
+}
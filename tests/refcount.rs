@@ -0,0 +1,44 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::mem::ManuallyDrop;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(refcount = "refcount")]
+struct Shared {
+    refcount: AtomicUsize,
+    data: *mut Counted,
+}
+
+#[test]
+fn test_refcount() {
+    let shared = Box::into_raw(Box::new(Shared {
+        refcount: AtomicUsize::new(3),
+        data: Box::into_raw(Box::new(Counted)),
+    }));
+
+    // Release every reference, only the last one frees the fields
+    for remaining in (0..3).rev() {
+        unsafe {
+            std::ptr::drop_in_place(shared);
+        }
+        let freed = if remaining == 0 { 1 } else { 0 };
+        assert_eq!(DROPPED.load(Ordering::SeqCst), freed);
+    }
+
+    // Free the structure itself, without dropping it again
+    unsafe {
+        let _ = Box::from_raw(shared as *mut ManuallyDrop<Shared>);
+    }
+}
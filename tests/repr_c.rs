@@ -0,0 +1,37 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{ffi_destruct, Destruct};
+use std::ffi::{c_char, CString};
+
+#[repr(C)]
+#[derive(Destruct)]
+#[destruct(require_repr_c)]
+pub struct Structure {
+    name: *mut c_char,
+}
+
+#[derive(Destruct)]
+#[destruct(require_repr_c)]
+#[repr(transparent)]
+pub struct Wrapper {
+    name: *mut c_char,
+}
+
+#[ffi_destruct(repr_c)]
+#[destruct(require_repr_c)]
+pub struct Generated {
+    name: *mut c_char,
+}
+
+#[test]
+fn test_require_repr_c() {
+    drop(Structure {
+        name: CString::new("structure").unwrap().into_raw(),
+    });
+    drop(Wrapper {
+        name: CString::new("wrapper").unwrap().into_raw(),
+    });
+    drop(Generated {
+        name: CString::new("generated").unwrap().into_raw(),
+    });
+}
@@ -0,0 +1,57 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{destruct_result, extern_c_destructor, Destruct};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+destruct_result!();
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted {
+    panics: bool,
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+        if self.panics {
+            panic!("nested drop panicked");
+        }
+    }
+}
+
+#[derive(Destruct)]
+pub struct Status {
+    data: *mut Counted,
+}
+
+extern_c_destructor!(Status, result);
+
+fn status(panics: bool) -> *mut Status {
+    Box::into_raw(Box::new(Status {
+        data: Box::into_raw(Box::new(Counted { panics })),
+    }))
+}
+
+#[test]
+fn test_result() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    unsafe {
+        assert_eq!(destruct_status(status(false)), DestructResult::Ok);
+        assert_eq!(
+            destruct_status(std::ptr::null_mut()),
+            DestructResult::NullPtr
+        );
+        assert_eq!(destruct_status(status(true)), DestructResult::PanicCaught);
+    }
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+
+    // Stable ABI
+    assert_eq!(DestructResult::Ok as i32, 0);
+    assert_eq!(DestructResult::NullPtr as i32, 1);
+    assert_eq!(DestructResult::PanicCaught as i32, 2);
+    assert_eq!(
+        std::mem::size_of::<DestructResult>(),
+        std::mem::size_of::<std::ffi::c_int>()
+    );
+}
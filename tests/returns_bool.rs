@@ -0,0 +1,58 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_destructor, Destruct};
+use std::ffi::{c_char, CString};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+pub struct Structure {
+    name: *mut c_char,
+    data: *mut Counted,
+}
+
+extern_c_destructor!(Structure, returns_bool);
+extern_c_destructor!(
+    Structure,
+    name = "destruct_structure_v2",
+    abi_version = 2,
+    returns_bool
+);
+
+fn structure() -> *mut Structure {
+    Box::into_raw(Box::new(Structure {
+        name: CString::new("name").unwrap().into_raw(),
+        data: Box::into_raw(Box::new(Counted)),
+    }))
+}
+
+#[test]
+fn test_returns_bool() {
+    unsafe {
+        // `true` once freed
+        assert!(destruct_structure(structure()));
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+        // `false` for null, nothing is freed
+        assert!(!destruct_structure(std::ptr::null_mut()));
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+
+        assert!(destruct_structure_v2(structure(), 2));
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+        assert!(!destruct_structure_v2(std::ptr::null_mut(), 2));
+        // `false` for a mismatched ABI version, the structure is left to the caller
+        let skipped = structure();
+        assert!(!destruct_structure_v2(skipped, 1));
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+        assert!(destruct_structure(skipped));
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+    }
+}
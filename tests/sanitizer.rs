@@ -0,0 +1,50 @@
+//! Allocation and free pairs checked by AddressSanitizer and LeakSanitizer:
+//! `RUSTFLAGS=-Zsanitizer=address cargo +nightly test --test sanitizer --target x86_64-unknown-linux-gnu`
+//!
+//! Without the sanitizer, these are plain tests of the default destructors.
+#![allow(dead_code, unused)]
+
+use ffi_destruct::{extern_c_destructor, Destruct};
+use std::ffi::{c_char, c_void, CString};
+
+#[derive(Destruct)]
+pub struct Owner {
+    name: *mut c_char,
+    value: *mut u64,
+    #[array(len = "len")]
+    values: *mut u32,
+    len: usize,
+    #[nullable]
+    child: *mut Owner,
+    #[drop_with = "free_value"]
+    custom: *mut u64,
+}
+
+extern_c_destructor!(Owner);
+
+/// Freed with the allocator it was allocated with, so the sanitizer matches both.
+unsafe fn free_value(ptr: *mut c_void) {
+    let _ = Box::from_raw(ptr as *mut u64);
+}
+
+fn owner(child: *mut Owner) -> *mut Owner {
+    let values = vec![1u32, 2, 3].into_boxed_slice();
+    Box::into_raw(Box::new(Owner {
+        name: CString::new("owner").unwrap().into_raw(),
+        value: Box::into_raw(Box::new(1)),
+        values: Box::into_raw(values) as *mut u32,
+        len: 3,
+        child,
+        custom: Box::into_raw(Box::new(2)),
+    }))
+}
+
+#[test]
+fn test_no_leaks() {
+    // Leaks are reported by LeakSanitizer when the test binary exits
+    for _ in 0..100 {
+        unsafe {
+            destruct_owner(owner(owner(std::ptr::null_mut())));
+        }
+    }
+}
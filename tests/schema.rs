@@ -0,0 +1,56 @@
+#![cfg(feature = "schema")]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::*;
+
+struct Data(u32);
+
+#[derive(Destruct)]
+struct Described {
+    name: *mut c_char,
+    #[nullable]
+    data: *mut Data,
+    #[array(len = "len")]
+    items: *mut Data,
+    len: usize,
+    #[no_drop]
+    borrowed: *const Data,
+}
+
+#[test]
+fn test_schema() {
+    assert_eq!(
+        Described::DESTRUCT_SCHEMA,
+        concat!(
+            r#"{"type":"Described","fields":["#,
+            r#"{"name":"name","free":"c_string","nullable":false},"#,
+            r#"{"name":"data","free":"box","nullable":true},"#,
+            r#"{"name":"items","free":"array","nullable":false,"len":"self.len"},"#,
+            r#"{"name":"len","free":"none"},"#,
+            r#"{"name":"borrowed","free":"no_drop","nullable":false}"#,
+            r#"]}"#
+        )
+    );
+}
+
+#[derive(Destruct)]
+#[destruct(nullable)]
+struct Defaulted {
+    name: *mut c_char,
+    #[non_null]
+    data: *mut Data,
+}
+
+#[test]
+fn test_schema_nullable_default() {
+    assert_eq!(
+        Defaulted::DESTRUCT_SCHEMA,
+        concat!(
+            r#"{"type":"Defaulted","fields":["#,
+            r#"{"name":"name","free":"c_string","nullable":true},"#,
+            r#"{"name":"data","free":"box","nullable":false}"#,
+            r#"]}"#
+        )
+    );
+}
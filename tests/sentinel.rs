@@ -0,0 +1,49 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// The invalid handle of a C library.
+const INVALID: *mut Counted = usize::MAX as *mut Counted;
+
+#[derive(Destruct)]
+#[destruct(report)]
+struct Handles {
+    #[nullable(sentinel = "crate::INVALID")]
+    handle: *mut Counted,
+    #[nullable(sentinel = "usize::MAX as *mut _")]
+    other: *mut Counted,
+}
+
+#[test]
+fn test_sentinel() {
+    let handles = Handles {
+        handle: INVALID,
+        other: Box::into_raw(Box::new(Counted)),
+    };
+    assert_eq!(handles.destruct_count(), 1);
+    drop(handles);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+
+    drop(Handles {
+        handle: Box::into_raw(Box::new(Counted)),
+        other: std::ptr::null_mut(),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+
+    drop(Handles {
+        handle: std::ptr::null_mut(),
+        other: usize::MAX as *mut Counted,
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+}
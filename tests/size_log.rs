@@ -0,0 +1,29 @@
+#![cfg(feature = "size-log")]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, CString};
+
+#[derive(Destruct)]
+struct Buffers {
+    string: *mut c_char,
+    value: *mut u64,
+    #[array(len = "self.len")]
+    values: *mut u32,
+    len: usize,
+    #[nullable]
+    missing: *mut u64,
+}
+
+#[test]
+fn test_size_log() {
+    let values = vec![1u32, 2, 3].into_boxed_slice();
+    // Logs `destruct: Buffers freed 26 bytes`
+    drop(Buffers {
+        string: CString::new("Hello").unwrap().into_raw(),
+        value: Box::into_raw(Box::new(1)),
+        values: Box::into_raw(values) as *mut u32,
+        len: 3,
+        missing: std::ptr::null_mut(),
+    });
+}
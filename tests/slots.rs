@@ -0,0 +1,37 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, CString};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Node;
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(report)]
+struct Slots {
+    nodes: [*mut Node; 4],
+    names: [*const c_char; 2],
+}
+
+#[test]
+fn test_slots() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let node = || Box::into_raw(Box::new(Node));
+    let null = std::ptr::null_mut();
+    // Partially filled
+    let slots = Slots {
+        nodes: [node(), null, node(), null],
+        names: [CString::new("name").unwrap().into_raw(), std::ptr::null()],
+    };
+    assert_eq!(slots.destruct_count(), 3);
+    drop(slots);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+}
@@ -0,0 +1,56 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, c_int, CString};
+use std::marker::PhantomData;
+use std::sync::atomic::AtomicUsize;
+
+/// Freed by its own `Drop`, unknown to the derive.
+struct Session;
+
+#[derive(Destruct)]
+#[destruct(strict)]
+struct Strict {
+    c_string: *mut c_char,
+    owned: CString,
+    bytes: Vec<u8>,
+    boxed: Box<[u32]>,
+    names: Option<Vec<String>>,
+    callback: Option<extern "C" fn(c_int)>,
+    refcount: AtomicUsize,
+    pair: (u32, [f64; 2]),
+    #[no_drop]
+    session: Session,
+}
+
+#[test]
+fn test_strict() {
+    drop(Strict {
+        c_string: CString::new("c_string").unwrap().into_raw(),
+        owned: CString::new("owned").unwrap(),
+        bytes: vec![1, 2, 3],
+        boxed: Box::new([1, 2]),
+        names: Some(vec![String::from("name")]),
+        callback: None,
+        refcount: AtomicUsize::new(1),
+        pair: (1, [2.0, 3.0]),
+        session: Session,
+    });
+}
+
+#[derive(Destruct)]
+#[destruct(strict)]
+struct Marked<T> {
+    value: *mut T,
+    _marker: PhantomData<*const u8>,
+    _owner: PhantomData<T>,
+}
+
+#[test]
+fn test_strict_phantom_data() {
+    drop(Marked {
+        value: Box::into_raw(Box::new(1u32)),
+        _marker: PhantomData,
+        _owner: PhantomData,
+    });
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "trace")]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::process::Command;
+
+#[derive(Destruct)]
+struct Traced {
+    #[drop_order(1)]
+    first: *mut u8,
+    second: *mut u8,
+    #[nullable]
+    missing: *mut u8,
+}
+
+#[test]
+fn test_trace_child() {
+    if std::env::var_os("FFI_DESTRUCT_TRACE_CHILD").is_none() {
+        return;
+    }
+    drop(Traced {
+        first: Box::into_raw(Box::new(1)),
+        second: Box::into_raw(Box::new(2)),
+        missing: std::ptr::null_mut(),
+    });
+}
+
+#[test]
+fn test_trace() {
+    // The harness captures stderr, so read it from a child running the test above
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "test_trace_child",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env("FFI_DESTRUCT_TRACE_CHILD", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let messages = stderr
+        .lines()
+        .filter(|line| line.starts_with("destruct: Traced::"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        ["destruct: Traced::second", "destruct: Traced::first"]
+    );
+}
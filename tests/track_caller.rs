@@ -0,0 +1,95 @@
+#![cfg(debug_assertions)]
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Serializes the tests replacing the panic hook.
+static HOOK: Mutex<()> = Mutex::new(());
+
+#[derive(Destruct)]
+struct Partial {
+    #[owned]
+    #[owned_if = "owns"]
+    data: *mut u8,
+    #[owned]
+    owns: bool,
+}
+
+#[test]
+fn test_track_caller() {
+    let _hook = HOOK.lock().unwrap();
+    let location = Arc::new(Mutex::new(None));
+    let hook_location = location.clone();
+    std::panic::set_hook(Box::new(move |info| {
+        let location = info.location().map(|l| (l.file().to_string(), l.line()));
+        *hook_location.lock().unwrap() = location;
+    }));
+
+    let ptr = Box::into_raw(Box::new(Partial {
+        data: std::ptr::null_mut(),
+        owns: true,
+    }));
+    let line = line!() + 1;
+    let result = std::panic::catch_unwind(|| unsafe { Partial::destruct_partial(ptr) });
+    let _ = std::panic::take_hook();
+
+    assert!(result.is_err());
+    let (file, panic_line) = location.lock().unwrap().take().unwrap();
+    assert!(file.ends_with("track_caller.rs"), "{}", file);
+    assert_eq!(panic_line, line);
+}
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(manual, canary = "magic", value = "0xDEAD")]
+struct Manual {
+    data: *mut Counted,
+    other: Counted,
+    magic: u32,
+}
+
+#[test]
+fn test_manual_destruct() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    Manual {
+        data: Box::into_raw(Box::new(Counted)),
+        other: Counted,
+        magic: 0xDEAD,
+    }
+    .destruct();
+    // The pointee and the field with its own `Drop`
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+
+    let _hook = HOOK.lock().unwrap();
+    let location = Arc::new(Mutex::new(None));
+    let hook_location = location.clone();
+    std::panic::set_hook(Box::new(move |info| {
+        let location = info.location().map(|l| (l.file().to_string(), l.line()));
+        *hook_location.lock().unwrap() = location;
+    }));
+
+    let corrupted = Manual {
+        data: std::ptr::null_mut(),
+        other: Counted,
+        magic: 0,
+    };
+    let line = line!() + 1;
+    let result = std::panic::catch_unwind(|| corrupted.destruct());
+    let _ = std::panic::take_hook();
+
+    assert!(result.is_err());
+    let (file, panic_line) = location.lock().unwrap().take().unwrap();
+    assert!(file.ends_with("track_caller.rs"), "{}", file);
+    assert_eq!(panic_line, line);
+}
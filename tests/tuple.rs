@@ -0,0 +1,41 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, CString};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Bar;
+
+impl Drop for Bar {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Foo(*mut c_char, #[nullable] *mut Bar);
+
+#[derive(Destruct)]
+struct Borrowed(#[no_drop] *mut Bar, *mut Bar);
+
+#[test]
+fn test_tuple() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    drop(Foo(
+        CString::new("Hello").unwrap().into_raw(),
+        Box::into_raw(Box::new(Bar)),
+    ));
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+    drop(Foo(
+        CString::new("Hello").unwrap().into_raw(),
+        std::ptr::null_mut(),
+    ));
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+
+    // The first field is left to its owner
+    let mut borrowed = Bar;
+    drop(Borrowed(&mut borrowed, Box::into_raw(Box::new(Bar))));
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+}
@@ -0,0 +1,145 @@
+//! Compiles each program of `tests/ui`, which must fail, and compares its errors with the
+//! `.stderr` file next to it. Each program of `tests/expand` must compile, and its expansion is
+//! compared with the `.expanded.rs` file next to it. Set `UI_BLESS=1` to write the current
+//! output instead.
+//!
+//! The expansion needs the unstable `-Zunpretty=expanded`, so `expand` is ignored by default and
+//! run on nightly: `cargo +nightly test --test ui -- --ignored expand`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs};
+
+/// The programs of the directory, sorted by name.
+fn cases(dir: &str) -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut cases = fs::read_dir(root.join(dir))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .filter(|path| !path.to_string_lossy().ends_with(".expanded.rs"))
+        .collect::<Vec<_>>();
+    cases.sort();
+    cases
+}
+
+fn name(case: &Path) -> &str {
+    case.file_stem().unwrap().to_str().unwrap()
+}
+
+/// A crate of one binary per case, depending on this crate.
+fn project(dir: &str, cases: &[PathBuf]) -> PathBuf {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let project = Path::new(env!("CARGO_TARGET_TMPDIR")).join(dir);
+    fs::create_dir_all(&project).unwrap();
+    let mut manifest = format!(
+        "[package]\nname = \"ui\"\nversion = \"0.0.0\"\nedition = \"2021\"\npublish = false\n\n\
+         [workspace]\n\n[dependencies]\nffi-destruct = {{ path = {:?} }}\n",
+        root.display().to_string()
+    );
+    for case in cases {
+        manifest += &format!(
+            "\n[[bin]]\nname = {:?}\npath = {:?}\n",
+            name(case),
+            case.display().to_string()
+        );
+    }
+    fs::write(project.join("Cargo.toml"), manifest).unwrap();
+    // The same versions of the dependencies as this crate, without fetching
+    if let Ok(lock) = fs::read(root.join("Cargo.lock")) {
+        fs::write(project.join("Cargo.lock"), lock).unwrap();
+    }
+    project
+}
+
+/// Cargo in the project, without the flags of the outer build, e.g. of a sanitizer.
+fn cargo(project: &Path, args: &[&str]) -> Command {
+    let mut cargo = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    cargo
+        .args(args)
+        .current_dir(project)
+        .env_remove("RUSTFLAGS");
+    cargo
+}
+
+/// The output of the case, without the paths and the summary of cargo.
+fn normalize(output: &[u8]) -> String {
+    let prefix = format!("{}/", env!("CARGO_MANIFEST_DIR"));
+    let lines = String::from_utf8_lossy(output)
+        .replace(&prefix, "")
+        .lines()
+        .filter(|line| !line.starts_with("error: could not compile"))
+        .filter(|line| !line.starts_with("warning: `ui`"))
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{}\n", lines.trim())
+}
+
+/// Compare the output of the case with the expected file, or write it with `UI_BLESS`.
+fn check(case: &Path, extension: &str, actual: String, failures: &mut Vec<String>) {
+    let expected_path = case.with_extension(extension);
+    if env::var_os("UI_BLESS").is_some() {
+        fs::write(&expected_path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+    if expected != actual {
+        failures.push(format!(
+            "{}: expected\n{}\nactual\n{}",
+            name(case),
+            expected,
+            actual
+        ));
+    }
+}
+
+#[test]
+fn ui() {
+    let cases = cases("tests/ui");
+    let project = project("ui", &cases);
+    let mut failures = Vec::new();
+    for case in &cases {
+        // Built, since `check` skips the assertions of the generated constants
+        let args = ["build", "--quiet", "--color=never", "--bin", name(case)];
+        let output = cargo(&project, &args).output().unwrap();
+        let actual = normalize(&output.stderr);
+        if output.status.success() {
+            failures.push(format!("{}: compiled successfully\n{}", name(case), actual));
+            continue;
+        }
+        check(case, "stderr", actual, &mut failures);
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+#[ignore = "needs a nightly toolchain"]
+fn expand() {
+    let cases = cases("tests/expand");
+    let project = project("expand", &cases);
+    let mut failures = Vec::new();
+    for case in &cases {
+        let args = [
+            "rustc",
+            "--quiet",
+            "--bin",
+            name(case),
+            "--",
+            "-Zunpretty=expanded",
+        ];
+        let output = cargo(&project, &args).output().unwrap();
+        if !output.status.success() {
+            let stderr = normalize(&output.stderr);
+            failures.push(format!("{}: failed to compile\n{}", name(case), stderr));
+            continue;
+        }
+        check(
+            case,
+            "expanded.rs",
+            normalize(&output.stdout),
+            &mut failures,
+        );
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
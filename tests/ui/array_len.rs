@@ -0,0 +1,17 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Missing {
+    #[array(require_drop)]
+    items: *mut String,
+    len: usize,
+}
+
+#[derive(Destruct)]
+struct Unsupported {
+    #[array(len = "len", size = 4)]
+    items: *mut String,
+    len: usize,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+#[destruct(builder)]
+#[destruct(iterative, child = "children", len = "len")]
+struct Node {
+    children: *mut *mut Node,
+    len: usize,
+}
+
+fn main() {}
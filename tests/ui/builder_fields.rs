@@ -0,0 +1,7 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+#[destruct(builder)]
+struct Pair(*mut u8, *mut u8);
+
+fn main() {}
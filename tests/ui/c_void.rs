@@ -0,0 +1,8 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Structure {
+    handle: *mut std::ffi::c_void,
+}
+
+fn main() {}
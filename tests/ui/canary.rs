@@ -0,0 +1,10 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+#[destruct(canary = "magic")]
+struct Checked {
+    magic: u32,
+    ptr: *mut u8,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use ffi_destruct::Destruct;
+
+unsafe fn free(_: *mut std::ffi::c_void) {}
+
+#[derive(Destruct)]
+struct Ignored {
+    #[nullable]
+    #[no_drop]
+    data: *mut u8,
+}
+
+#[derive(Destruct)]
+struct TwoWays {
+    #[c_string]
+    #[drop_with = "free"]
+    name: *mut u8,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use ffi_destruct::Destruct;
+use std::sync::mpsc::Sender;
+
+#[derive(Destruct)]
+#[destruct(defer, notify_sender = "done")]
+struct Notified {
+    ptr: *mut u8,
+    done: Sender<()>,
+}
+
+fn main() {}
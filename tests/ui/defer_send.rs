@@ -0,0 +1,10 @@
+use ffi_destruct::Destruct;
+use std::rc::Rc;
+
+#[derive(Destruct)]
+#[destruct(defer)]
+struct Shared {
+    counter: *mut Rc<u8>,
+}
+
+fn main() {}
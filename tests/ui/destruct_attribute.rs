@@ -0,0 +1,16 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+#[destruct(nullable, unknown)]
+struct Unknown {
+    ptr: *mut u8,
+}
+
+#[derive(Destruct)]
+#[destruct(refcount = 1)]
+struct NotAString {
+    ptr: *mut u8,
+    refcount: std::sync::atomic::AtomicUsize,
+}
+
+fn main() {}
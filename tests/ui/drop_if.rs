@@ -0,0 +1,17 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Conditional {
+    #[drop_if = "self.owned &&"]
+    ptr: *mut u8,
+    owned: bool,
+}
+
+#[derive(Destruct)]
+struct NotAString {
+    #[drop_if(self.owned)]
+    ptr: *mut u8,
+    owned: bool,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct NotAnInteger {
+    #[drop_order("first")]
+    ptr: *mut u8,
+}
+
+#[derive(Destruct)]
+struct OutOfRange {
+    #[drop_order(4294967296)]
+    ptr: *mut u8,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![deny(deprecated)]
+#![allow(dead_code)]
+
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Structure {
+    #[nullable]
+    #[nullable]
+    data: *mut u8,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+enum Handle {
+    Empty,
+    Allocated(*mut u8),
+}
+
+fn main() {}
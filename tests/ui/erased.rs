@@ -0,0 +1,22 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Missing {
+    #[erased]
+    payload: *mut std::ffi::c_void,
+}
+
+#[derive(Destruct)]
+struct Unsupported {
+    #[erased(drop = "drop_payload")]
+    payload: *mut std::ffi::c_void,
+    drop_payload: unsafe fn(*mut std::ffi::c_void),
+}
+
+#[derive(Destruct)]
+struct Empty {
+    #[erased()]
+    payload: *mut std::ffi::c_void,
+}
+
+fn main() {}
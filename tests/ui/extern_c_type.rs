@@ -0,0 +1,10 @@
+use ffi_destruct::{extern_c_destructor, Destruct};
+
+#[derive(Destruct)]
+pub struct Structure {
+    field: *mut u8,
+}
+
+extern_c_destructor!(&Structure);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+#[destruct(iterative, child = "children")]
+struct Node {
+    children: *mut *mut Node,
+    len: usize,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Structure {
+    #[ptr(box = "Box<u8>")]
+    arguments: *mut u8,
+    #[ptr(rc = "Rc")]
+    unsupported: *mut u8,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Structure {
+    #[nullable]
+    len: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Structure {
+    #[reclaim = "&u32"]
+    inner: *const u32,
+}
+
+fn main() {}
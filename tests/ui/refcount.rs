@@ -0,0 +1,18 @@
+use ffi_destruct::Destruct;
+use std::sync::atomic::AtomicUsize;
+
+#[derive(Destruct)]
+#[destruct(refcount = "refcount")]
+struct Shared {
+    refcount: AtomicUsize,
+    data: *mut u8,
+    name: String,
+}
+
+fn main() {
+    drop(Shared {
+        refcount: AtomicUsize::new(1),
+        data: std::ptr::null_mut(),
+        name: String::new(),
+    });
+}
@@ -0,0 +1,14 @@
+use ffi_destruct::Destruct;
+
+#[repr(C)]
+struct VTable {
+    release: unsafe extern "C" fn(*mut Object),
+}
+
+#[derive(Destruct)]
+#[release_via = "self.vtable"]
+struct Object {
+    vtable: *const VTable,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+#[destruct(require_repr_c)]
+struct Structure {
+    data: *mut u8,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Unsupported {
+    #[nullable(invalid = "usize::MAX as *mut u8")]
+    ptr: *mut u8,
+}
+
+#[derive(Destruct)]
+struct InvalidExpression {
+    #[nullable(sentinel = "usize::MAX + ")]
+    ptr: *mut u8,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use ffi_destruct::Destruct;
+
+type Handle = *mut u8;
+
+#[derive(Destruct)]
+#[destruct(strict)]
+struct Structure {
+    data: *mut u8,
+    handle: Handle,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+union Both {
+    #[union_active]
+    string: *mut std::ffi::c_char,
+    #[union_active]
+    number: *mut u64,
+}
+
+#[derive(Destruct)]
+union Neither {
+    string: *mut std::ffi::c_char,
+    number: *mut u64,
+}
+
+#[derive(Destruct)]
+struct NotAUnion {
+    #[union_active]
+    string: *mut std::ffi::c_char,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct Buffer {
+    #[vec(len = "len")]
+    items: *mut String,
+    len: usize,
+}
+
+fn main() {}
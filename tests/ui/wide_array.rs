@@ -0,0 +1,16 @@
+use ffi_destruct::Destruct;
+
+#[derive(Destruct)]
+struct WideBytes {
+    #[wide]
+    #[array(len = "len")]
+    sized: *mut u8,
+    len: usize,
+}
+
+fn main() {
+    drop(WideBytes {
+        sized: std::ptr::null_mut(),
+        len: 0,
+    });
+}
@@ -0,0 +1,40 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, CString};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(report)]
+union Value {
+    #[union_active]
+    #[nullable]
+    counted: *mut Counted,
+    string: *mut c_char,
+}
+
+#[test]
+fn test_union() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let value = Value {
+        counted: Box::into_raw(Box::new(Counted)),
+    };
+    assert_eq!(value.destruct_count(), 1);
+    drop(value);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+
+    drop(Value {
+        counted: std::ptr::null_mut(),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+}
@@ -0,0 +1,64 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+trait Shape {
+    fn sides(&self) -> u32;
+}
+
+struct Square;
+
+impl Shape for Square {
+    fn sides(&self) -> u32 {
+        4
+    }
+}
+
+impl Drop for Square {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+struct Erased<T: ?Sized> {
+    ptr: *mut T,
+}
+
+#[derive(Destruct)]
+struct Slices {
+    bytes: *mut [u8],
+    #[nullable]
+    shape: *const dyn Shape,
+}
+
+#[test]
+fn test_unsized() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let shape: Box<dyn Shape> = Box::new(Square);
+    drop(Erased {
+        ptr: Box::into_raw(shape),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+
+    let bytes: Box<[u8]> = vec![1, 2, 3].into_boxed_slice();
+    drop(Erased::<[u8]> {
+        ptr: Box::into_raw(bytes),
+    });
+
+    drop(Erased {
+        ptr: Box::into_raw(Box::new(Square)),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 2);
+
+    // Fat pointer fields without generics
+    let shape: Box<dyn Shape> = Box::new(Square);
+    drop(Slices {
+        bytes: Box::into_raw(vec![1, 2, 3].into_boxed_slice()),
+        shape: Box::into_raw(shape),
+    });
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 3);
+}
@@ -0,0 +1,43 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static RELEASED: AtomicUsize = AtomicUsize::new(0);
+
+#[repr(C)]
+pub struct VTable {
+    release: unsafe extern "C" fn(*mut c_void),
+}
+
+unsafe extern "C" fn release(object: *mut c_void) {
+    let object = &*(object as *const Object);
+    RELEASED.fetch_add(object.id, Ordering::SeqCst);
+}
+
+static VTABLE: VTable = VTable { release };
+
+#[derive(Destruct)]
+#[release_via = "self.vtable, release"]
+#[repr(C)]
+pub struct Object {
+    #[no_drop]
+    vtable: *const VTable,
+    id: usize,
+}
+
+#[test]
+fn test_release_via() {
+    drop(Object {
+        vtable: &VTABLE,
+        id: 42,
+    });
+    assert_eq!(RELEASED.load(Ordering::SeqCst), 42);
+
+    drop(Object {
+        vtable: std::ptr::null(),
+        id: 1,
+    });
+    assert_eq!(RELEASED.load(Ordering::SeqCst), 42);
+}
@@ -0,0 +1,39 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+
+/// Allocate a NUL-terminated UTF-16 string, as handed to Windows APIs.
+fn wide(value: &str) -> *mut u16 {
+    let units: Vec<u16> = value.encode_utf16().chain(Some(0)).collect();
+    Box::into_raw(units.into_boxed_slice()) as *mut u16
+}
+
+#[derive(Destruct)]
+struct WideStrings {
+    #[wide]
+    name: *mut u16,
+    #[wide]
+    #[nullable]
+    title: *const u16,
+    #[wide]
+    #[array(len = "len")]
+    sized: *mut u16,
+    len: usize,
+}
+
+#[test]
+fn test_wide() {
+    // Freed without leaking, checked by LeakSanitizer in CI
+    drop(WideStrings {
+        name: wide("name"),
+        title: wide("タイトル"),
+        sized: wide("sized"),
+        len: 6,
+    });
+    drop(WideStrings {
+        name: wide(""),
+        title: std::ptr::null(),
+        sized: wide(""),
+        len: 1,
+    });
+}
@@ -0,0 +1,36 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, CString};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod ffi {
+    use super::*;
+
+    pub static FREED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Frees the members, like a C cleanup function.
+    #[allow(non_snake_case)]
+    pub unsafe extern "C" fn MyStruct_free(ptr: *mut MyStruct) {
+        FREED.fetch_add(1, Ordering::SeqCst);
+        let _ = CString::from_raw((*ptr).name);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(with = "ffi::MyStruct_free")]
+pub struct MyStruct {
+    name: *mut c_char,
+    // Not freed by the derive, the function owns the cleanup
+    borrowed: *mut u8,
+}
+
+#[test]
+fn test_with() {
+    let mut borrowed = 0;
+    drop(MyStruct {
+        name: CString::new("name").unwrap().into_raw(),
+        borrowed: &mut borrowed,
+    });
+    assert_eq!(ffi::FREED.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,41 @@
+#![allow(dead_code, unused)]
+
+use ffi_destruct::Destruct;
+use std::ffi::{c_char, CString};
+use std::mem::ManuallyDrop;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Destruct)]
+#[destruct(nullable, zero_on_drop)]
+struct Zeroed {
+    a: *mut Counted,
+    b: *const c_char,
+}
+
+#[test]
+fn test_zero_on_drop() {
+    let before = DROPPED.load(Ordering::SeqCst);
+    let mut zeroed = ManuallyDrop::new(Zeroed {
+        a: Box::into_raw(Box::new(Counted)),
+        b: CString::new("b").unwrap().into_raw(),
+    });
+    // The fields stay readable after running the destructor in place
+    unsafe { ManuallyDrop::drop(&mut zeroed) };
+    assert!(zeroed.a.is_null());
+    assert!(zeroed.b.is_null());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+
+    // A second drop finds nothing to free
+    unsafe { ManuallyDrop::drop(&mut zeroed) };
+    assert_eq!(DROPPED.load(Ordering::SeqCst), before + 1);
+}